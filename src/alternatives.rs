@@ -0,0 +1,282 @@
+use std::{
+    cmp,
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use enum_map::EnumMap;
+
+use crate::{
+    constraints::Constraints,
+    search::{
+        is_better, moves, press_time, record_steps, step_value, Objective, SearchOptions,
+        StepRecord,
+    },
+    stats::Stats,
+    Action, ActionName, Player, Step,
+};
+
+/// One of the best rotations found by [`search`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternative {
+    pub steps: Vec<StepRecord>,
+    pub damage: u32,
+    pub time: u32,
+    /// Damage counted by the objective, which differs from `damage` for burst windows
+    pub value: u32,
+    /// Leading actions pressed at the same times as in the best rotation
+    pub shared: usize,
+    /// When this rotation first presses something other than the best one does, `None` for the
+    /// best rotation itself
+    pub diverges_at: Option<u32>,
+}
+
+impl Alternative {
+    pub fn dps(&self) -> f64 {
+        if self.time == 0 {
+            0.0
+        } else {
+            self.damage as f64 / self.time as f64 * 1000.0
+        }
+    }
+}
+
+/// The best distinct rotations found by [`search`], best first
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlternativesResult {
+    pub objective: Objective,
+    pub horizon: u32,
+    pub alternatives: Vec<Alternative>,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+/// One of the best paths to a state: its value, the path to the parent it extends and the step
+/// taken from there
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    value: u32,
+    parent: (usize, usize),
+    step: Step,
+    /// Id in [`Rotations`] of the actions pressed along the path and their press times, which
+    /// tells apart paths that only differ in their waits from really different rotations
+    rotation: usize,
+}
+
+/// Every rotation reached so far as a trie: a rotation is the one it extends with one more action
+/// pressed at some time, and gets an id the first time it is reached. The empty rotation is 0.
+#[derive(Debug, Default)]
+struct Rotations {
+    ids: HashMap<(usize, ActionName, u32), usize>,
+}
+
+impl Rotations {
+    /// Id of the rotation after pressing the step at `time`. Waits press nothing, so they keep
+    /// the rotation.
+    fn extend(&mut self, rotation: usize, step: &Step, time: u32) -> usize {
+        match step {
+            Step::Action(action_name) => {
+                let id = self.ids.len() + 1;
+                *self.ids.entry((rotation, *action_name, time)).or_insert(id)
+            }
+            Step::Wait(_) => rotation,
+        }
+    }
+}
+
+/// Adds the entry to the best paths of a state, keeping the `count` best distinct ones
+fn insert(entries: &mut Vec<Entry>, entry: Entry, count: usize) {
+    match entries
+        .iter_mut()
+        .find(|other| other.rotation == entry.rotation)
+    {
+        Some(other) if entry.value > other.value => *other = entry,
+        Some(_) => return,
+        None => entries.push(entry),
+    }
+    entries.sort_by_key(|entry| cmp::Reverse(entry.value));
+    entries.truncate(count);
+}
+
+/// Orders `(value, time)` pairs best first under the objective, ties going to the earlier one
+fn compare(objective: Objective, a: (u32, u32), b: (u32, u32)) -> cmp::Ordering {
+    if is_better(objective, a, b) {
+        cmp::Ordering::Less
+    } else if is_better(objective, b, a) {
+        cmp::Ordering::Greater
+    } else {
+        a.1.cmp(&b.1)
+    }
+}
+
+/// Actions pressed along the steps with their press times, skipping waits
+fn pressed(steps: &[StepRecord]) -> Vec<(Step, u32)> {
+    steps
+        .iter()
+        .filter(|record| matches!(record.step, Step::Action(_)))
+        .map(|record| (record.step, record.time))
+        .collect()
+}
+
+/// Finds the `count` best distinct rotations among those ending by the horizon.
+///
+/// Like the best-first search this visits every reachable state, but it keeps the `count` best
+/// paths to each state instead of only the best one. States are expanded in time order, so every
+/// parent of a state is expanded before it and its paths are final by then. Two paths are the
+/// same rotation when they press the same actions at the same times, whatever they wait for in
+/// between.
+///
+/// Only rotations that cannot be extended count: those ending in a state with no step left
+/// before the horizon, past the end of a burst window, or, for `TimeTo`, at the first step
+/// that reaches the target. Anything else is a shorter version of a rotation that does at least
+/// as well. Each rotation is compared with the best one to find how much of it they share.
+pub fn search(
+    actions_map: &EnumMap<ActionName, Action>,
+    stats: Stats,
+    options: &SearchOptions,
+    constraints: &Constraints,
+    count: usize,
+) -> AlternativesResult {
+    let start = Instant::now();
+    let objective = options.objective;
+    let player = Player::new(stats, actions_map);
+    let initial = player.clone();
+
+    // Every visited state gets an id, which indexes `entries`
+    let mut visited = HashMap::new();
+    let mut entries = vec![];
+    let mut rotations = Rotations::default();
+    let progress = constraints.start();
+    visited.insert(constraints.state_key(&player, &progress), 0);
+    entries.push(vec![Entry {
+        value: 0,
+        parent: (0, 0),
+        step: Step::Action(ActionName::None),
+        rotation: 0,
+    }]);
+
+    let mut pending = BTreeMap::new();
+    pending.insert((player.time, 0), (player, progress));
+
+    let mut cnt = 0;
+    // Paths that end a rotation, as (value, time, state id, rank, rotation id)
+    let mut ends = vec![];
+
+    while let Some(((_, id), (player, progress))) = pending.pop_first() {
+        cnt += 1;
+        let complete = constraints.is_complete(&progress);
+        let reached = |entry: &Entry| match objective {
+            Objective::TimeTo(target) => entry.value >= target,
+            _ => false,
+        };
+        let finished = match objective {
+            // Nothing pressed after the window can add to it
+            Objective::Burst { end, .. } => player.time >= end,
+            // Every path here has reached the target or none is left
+            _ => entries[id].iter().all(reached),
+        };
+
+        let mut extended = false;
+        if !finished {
+            for step in constraints.moves(&progress, moves(&player)) {
+                let new_player = match player.apply_step(&step, actions_map) {
+                    Ok(new_player) if new_player.time <= options.horizon => new_player,
+                    _ => continue,
+                };
+                let time = press_time(actions_map, &player, &step);
+                let new_progress =
+                    match constraints.advance(actions_map, &progress, &step, time, &new_player) {
+                        Some(progress) => progress,
+                        None => continue,
+                    };
+                extended = true;
+                let gain = step_value(objective, actions_map, &player, &step, &new_player, 0);
+                let key = constraints.state_key(&new_player, &new_progress);
+                let new_id = *visited.entry(key).or_insert_with(|| {
+                    entries.push(vec![]);
+                    pending.insert(
+                        (new_player.time, entries.len() - 1),
+                        (new_player, new_progress),
+                    );
+                    entries.len() - 1
+                });
+                for rank in 0..entries[id].len() {
+                    let entry = entries[id][rank];
+                    if reached(&entry) {
+                        continue;
+                    }
+                    let new_entry = Entry {
+                        value: entry.value + gain,
+                        parent: (id, rank),
+                        step,
+                        rotation: rotations.extend(entry.rotation, &step, time),
+                    };
+                    insert(&mut entries[new_id], new_entry, count);
+                }
+            }
+        }
+
+        if complete {
+            for (rank, entry) in entries[id].iter().enumerate() {
+                if !extended || finished || reached(entry) {
+                    ends.push((entry.value, player.time, id, rank, entry.rotation));
+                }
+            }
+        }
+    }
+
+    ends.sort_by(|a, b| compare(objective, (a.0, a.1), (b.0, b.1)));
+    let mut seen = HashSet::new();
+    let mut alternatives: Vec<Alternative> = vec![];
+    for (value, _, id, rank, rotation) in ends {
+        if alternatives.len() == count {
+            break;
+        }
+        if !seen.insert(rotation) {
+            continue;
+        }
+
+        let mut path = vec![];
+        let mut current = (id, rank);
+        loop {
+            let entry = entries[current.0][current.1];
+            if let Step::Action(ActionName::None) = entry.step {
+                break;
+            }
+            path.push(entry.step);
+            current = entry.parent;
+        }
+        path.reverse();
+        let (steps, last) = record_steps(actions_map, initial.clone(), &path);
+
+        let (shared, diverges_at) = match alternatives.first() {
+            None => (pressed(&steps).len(), None),
+            Some(best) => {
+                let (ours, theirs) = (pressed(&steps), pressed(&best.steps));
+                let shared = ours.iter().zip(&theirs).take_while(|(a, b)| a == b).count();
+                let diverges_at = [ours.get(shared), theirs.get(shared)]
+                    .into_iter()
+                    .flatten()
+                    .map(|(_, time)| *time)
+                    .min();
+                (shared, diverges_at)
+            }
+        };
+        alternatives.push(Alternative {
+            steps,
+            damage: last.damage,
+            time: last.time,
+            value,
+            shared,
+            diverges_at,
+        });
+    }
+
+    AlternativesResult {
+        objective,
+        horizon: options.horizon,
+        alternatives,
+        nodes: cnt,
+        elapsed: start.elapsed(),
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
+    str::FromStr,
+    time::Instant,
+};
+
+use enum_map::EnumMap;
+
+use crate::{
+    bound::DamageBound,
+    constraints::{Constraints, Progress},
+    search::{
+        is_better, moves, press_time, record_steps, step_value, trace, Objective, SearchOptions,
+        SearchResult,
+    },
+    stats::Stats,
+    Action, ActionName, CooldownType, Player, StateKey, Step, GLOBAL_COOLDOWN_GROUP,
+};
+
+/// How beam search ranks the states that start a GCD slot
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BeamScore {
+    /// Value counted so far
+    Value,
+    /// Value counted so far per millisecond
+    Rate,
+    /// Value counted so far plus the upper bound on what the rest of the horizon can add
+    #[default]
+    Bound,
+}
+
+impl FromStr for BeamScore {
+    type Err = String;
+
+    /// Parses `value`, `rate` or `bound`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "value" => Ok(BeamScore::Value),
+            "rate" => Ok(BeamScore::Rate),
+            "bound" => Ok(BeamScore::Bound),
+            _ => Err(format!(
+                "unknown beam score `{}`, expected value, rate or bound",
+                text
+            )),
+        }
+    }
+}
+
+/// A state reached in the current slot, with the value of its best path and its id in the
+/// history
+#[derive(Debug, Clone)]
+struct BeamState {
+    value: u32,
+    id: usize,
+    player: Player,
+    progress: Progress,
+}
+
+/// States of one slot, each state key kept once with its best value
+#[derive(Debug, Default)]
+struct Pool {
+    states: Vec<BeamState>,
+    index: HashMap<StateKey, usize>,
+}
+
+impl Pool {
+    /// Adds the state reached from `parent`, or improves the path of the one already there.
+    /// Returns the index of a newly added state.
+    fn relax(
+        &mut self,
+        key: StateKey,
+        history: &mut Vec<(usize, Step)>,
+        (parent, step): (usize, Step),
+        value: u32,
+        (player, progress): (Player, Progress),
+    ) -> Option<usize> {
+        match self.index.entry(key) {
+            Entry::Vacant(entry) => {
+                let id = history.len();
+                history.push((parent, step));
+                entry.insert(self.states.len());
+                self.states.push(BeamState {
+                    value,
+                    id,
+                    player,
+                    progress,
+                });
+                Some(self.states.len() - 1)
+            }
+            Entry::Occupied(entry) => {
+                let state = &mut self.states[*entry.get()];
+                if value > state.value {
+                    state.value = value;
+                    history[state.id] = (parent, step);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Score of the state as a fraction, so rates compare exactly
+fn score(
+    score: BeamScore,
+    damage_bound: &DamageBound,
+    (origin, horizon): (u32, u32),
+    state: &BeamState,
+) -> (u64, u64) {
+    let value = state.value as u64;
+    match score {
+        BeamScore::Value => (value, 1),
+        BeamScore::Rate => (value, (state.player.time - origin).max(1) as u64),
+        BeamScore::Bound => (value + damage_bound.remaining(&state.player, horizon), 1),
+    }
+}
+
+/// What [`Beam::run`] makes of a visited state
+struct Visit {
+    /// Whether the state is the best one so far, whose path has to be kept
+    best: bool,
+    /// Whether to expand the state further
+    expand: bool,
+}
+
+/// Drops every state from the history that is not on the path to one of `ids`, and renumbers
+/// `ids` to match. The root stays at index 0.
+fn compact(history: &mut Vec<(usize, Step)>, ids: &mut [&mut usize]) {
+    const DROPPED: usize = usize::MAX;
+    let mut renumbered = vec![DROPPED; history.len()];
+    renumbered[0] = 0;
+    for id in ids.iter() {
+        let mut current = **id;
+        while renumbered[current] == DROPPED {
+            renumbered[current] = 0;
+            current = history[current].0;
+        }
+    }
+    let mut kept = vec![history[0]];
+    for id in 1..history.len() {
+        if renumbered[id] != DROPPED {
+            renumbered[id] = kept.len();
+            kept.push(history[id]);
+        }
+    }
+    for (parent, _) in &mut kept {
+        *parent = renumbered[*parent];
+    }
+    for id in ids.iter_mut() {
+        **id = renumbered[**id];
+    }
+    *history = kept;
+}
+
+/// Settings of one beam run
+struct Beam<'a> {
+    actions_map: &'a EnumMap<ActionName, Action>,
+    damage_bound: &'a DamageBound,
+    constraints: &'a Constraints,
+    objective: Objective,
+    horizon: u32,
+    width: usize,
+    score: BeamScore,
+}
+
+impl Beam<'_> {
+    /// Runs the beam from the player, whose path starts at index 0 of `history`, calling `visit`
+    /// on every state as it is expanded. Between slots the history is cut down to the paths of
+    /// the beam and of the best state, so it does not keep the candidates the beam drops. Returns
+    /// the number of states expanded, the largest number held at once and the id of the best
+    /// state, if `visit` marked any.
+    fn run(
+        &self,
+        player: Player,
+        history: &mut Vec<(usize, Step)>,
+        mut visit: impl FnMut(&BeamState) -> Visit,
+    ) -> (u64, usize, Option<usize>) {
+        let actions_map = self.actions_map;
+        let constraints = self.constraints;
+        let origin = player.time;
+        let mut beam = vec![BeamState {
+            value: 0,
+            id: 0,
+            player,
+            progress: constraints.start(),
+        }];
+        let mut cnt = 0;
+        let mut peak_heap = 1;
+        let mut best_id = None;
+        // Compacting only once the history has doubled keeps its cost linear overall
+        let mut compacted_len = history.len();
+
+        while !beam.is_empty() {
+            let mut slot = Pool::default();
+            let mut next = Pool::default();
+            let mut queue = BinaryHeap::new();
+            for state in beam {
+                let time = state.player.time;
+                let index = slot.states.len();
+                let key = constraints.state_key(&state.player, &state.progress);
+                slot.index.insert(key, index);
+                slot.states.push(state);
+                queue.push(Reverse((time, index)));
+            }
+
+            // Every step moves time forward, so all paths to a state are in before it is popped
+            while let Some(Reverse((_, index))) = queue.pop() {
+                let state = slot.states[index].clone();
+                cnt += 1;
+                let Visit { best, expand } = visit(&state);
+                if best {
+                    best_id = Some(state.id);
+                }
+                if !expand {
+                    continue;
+                }
+                let BeamState {
+                    value,
+                    id,
+                    player,
+                    progress,
+                } = state;
+
+                // Waiting on with the GCD ready only idles it, and would let a single slot run
+                // on to the horizon
+                let gcd_ready = player
+                    .recast_timer(GLOBAL_COOLDOWN_GROUP)
+                    .is_none_or(|timer| timer.wait_time() == 0);
+                let steps =
+                    moves(&player).filter(|step| !gcd_ready || !matches!(step, Step::Wait(_)));
+                for step in constraints.moves(&progress, steps) {
+                    let new_player = match player.apply_step(&step, actions_map) {
+                        Ok(new_player) if new_player.time <= self.horizon => new_player,
+                        _ => continue,
+                    };
+                    let time = press_time(actions_map, &player, &step);
+                    let new_progress =
+                        match constraints.advance(actions_map, &progress, &step, time, &new_player)
+                        {
+                            Some(new_progress) => new_progress,
+                            None => continue,
+                        };
+                    let new_value = step_value(
+                        self.objective,
+                        actions_map,
+                        &player,
+                        &step,
+                        &new_player,
+                        value,
+                    );
+                    let key = constraints.state_key(&new_player, &new_progress);
+                    let reached = (new_player, new_progress);
+                    let ends_slot = match step {
+                        Step::Action(action_name) => {
+                            actions_map[action_name].cooldown_type() != CooldownType::OffGlobal
+                        }
+                        Step::Wait(_) => false,
+                    };
+                    if ends_slot {
+                        next.relax(key, history, (id, step), new_value, reached);
+                    } else {
+                        let time = reached.0.time;
+                        if let Some(index) =
+                            slot.relax(key, history, (id, step), new_value, reached)
+                        {
+                            queue.push(Reverse((time, index)));
+                        }
+                    }
+                }
+                peak_heap = peak_heap.max(queue.len() + next.states.len());
+            }
+
+            let mut ranked: Vec<_> = next
+                .states
+                .into_iter()
+                .map(|state| {
+                    let score = score(
+                        self.score,
+                        self.damage_bound,
+                        (origin, self.horizon),
+                        &state,
+                    );
+                    (score, state)
+                })
+                .collect();
+            // Highest score first, then more damage, then earlier; the sort is stable so equal
+            // states keep the order they were reached in
+            ranked.sort_by(|((a, a_den), a_state), ((b, b_den), b_state)| {
+                (*b as u128 * *a_den as u128)
+                    .cmp(&(*a as u128 * *b_den as u128))
+                    .then(b_state.player.damage.cmp(&a_state.player.damage))
+                    .then(a_state.player.time.cmp(&b_state.player.time))
+            });
+            ranked.truncate(self.width);
+            beam = ranked.into_iter().map(|(_, state)| state).collect();
+
+            if history.len() > 2 * compacted_len {
+                let mut ids: Vec<_> = beam.iter_mut().map(|state| &mut state.id).collect();
+                ids.extend(best_id.as_mut());
+                compact(history, &mut ids);
+                compacted_len = history.len();
+            }
+        }
+        (cnt, peak_heap, best_id)
+    }
+}
+
+/// Approximate search that keeps at most `width` states per GCD slot.
+///
+/// A slot starts from the states of the beam and expands them with oGCDs and waits until a GCD
+/// is pressed, waiting only while the GCD is on cooldown. States reached within the slot are
+/// expanded in time order and merged on their state key, so each one is expanded once with its
+/// best value. The states right after a GCD make up the candidates for the next slot, of which
+/// the `width` best by `score` are kept. Every state reached is a candidate for the best
+/// rotation. Only the paths of the kept states and of the best one stay in memory, so it grows
+/// with the width times the number of slots rather than with the state space, but a rotation
+/// whose early slots score poorly is dropped and the optimum may be missed. Returns `None` if no
+/// state reached meets the constraints.
+pub fn search(
+    actions_map: &EnumMap<ActionName, Action>,
+    stats: Stats,
+    options: &SearchOptions,
+    constraints: &Constraints,
+    width: usize,
+    beam_score: BeamScore,
+) -> Option<SearchResult> {
+    let start = Instant::now();
+    let objective = options.objective;
+    let damage_bound = DamageBound::new(actions_map, &stats);
+    let player = Player::new(stats, actions_map);
+    let initial = player.clone();
+    let beam = Beam {
+        actions_map,
+        damage_bound: &damage_bound,
+        constraints,
+        objective,
+        horizon: options.horizon,
+        width,
+        score: beam_score,
+    };
+
+    let mut history = vec![(0, Step::Action(ActionName::None))];
+    let mut best = None;
+    let (cnt, peak_heap, best_id) = beam.run(player, &mut history, |state| {
+        let BeamState { value, player, .. } = state;
+        let complete = constraints.is_complete(&state.progress);
+        let improves =
+            complete && best.is_none_or(|best| is_better(objective, (*value, player.time), best));
+        if improves {
+            best = Some((*value, player.time));
+        }
+        let expand = match (objective, best) {
+            // Nothing from here can reach the target before the best already did
+            (Objective::TimeTo(target), Some((value, time))) => {
+                value < target || player.time < time
+            }
+            (Objective::Burst { end, .. }, _) => player.time < end,
+            _ => true,
+        };
+        Visit {
+            best: improves,
+            expand,
+        }
+    });
+
+    let (value, _) = best?;
+    let path = trace(&history, best_id?);
+    let (steps, last) = record_steps(actions_map, initial, &path);
+    Some(SearchResult {
+        objective,
+        horizon: options.horizon,
+        steps,
+        damage: last.damage,
+        time: last.time,
+        value,
+        nodes: cnt,
+        peak_heap,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Runs an unconstrained beam from the player up to the horizon. Returns the steps from the
+/// player to the state with the most damage dealt since it, and the number of states expanded.
+pub(crate) fn rotation_from(
+    actions_map: &EnumMap<ActionName, Action>,
+    player: Player,
+    horizon: u32,
+    width: usize,
+    score: BeamScore,
+) -> (Vec<Step>, u64) {
+    let damage_bound = DamageBound::new(actions_map, &player.stats);
+    let beam = Beam {
+        actions_map,
+        damage_bound: &damage_bound,
+        constraints: &Constraints::default(),
+        objective: Objective::Damage,
+        horizon,
+        width,
+        score,
+    };
+    let mut history = vec![(0, Step::Action(ActionName::None))];
+    let mut most = 0;
+    let (cnt, _, best_id) = beam.run(player, &mut history, |state| {
+        let best = state.value > most;
+        most = most.max(state.value);
+        Visit { best, expand: true }
+    });
+    let steps = best_id.map_or_else(Vec::new, |id| trace(&history, id));
+    (steps, cnt)
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+
+use enum_map::EnumMap;
+
+use crate::{
+    stats::Stats,
+    status::{Buffs, Modifier, StatusName},
+    Action, ActionName, AttackType, CooldownType, Player, RecastTimer, ANIMATION_LOCK,
+    GLOBAL_COOLDOWN_GROUP, SERVER_TICK,
+};
+
+/// Most damage a single use of an action can deal, with damage over time split out so it can be
+/// cut off at the end of the search
+#[derive(Debug, Clone, Default)]
+struct UseDamage {
+    hit: u32,
+    /// Damage and duration of each damage over time the action applies
+    ticks: Vec<(u32, u32)>,
+}
+
+impl UseDamage {
+    fn new(stats: &Stats, action: &Action, potency: u32, buffs: &Buffs) -> Self {
+        UseDamage {
+            hit: stats.expected_damage(potency, buffs),
+            ticks: action
+                .statuses
+                .iter()
+                .filter(|application| application.potency > 0)
+                .map(|application| {
+                    let damage = stats.expected_damage(application.potency, buffs);
+                    (damage, application.duration)
+                })
+                .collect(),
+        }
+    }
+
+    fn within(&self, remaining: u32) -> u64 {
+        let ticks: u64 = self
+            .ticks
+            .iter()
+            .map(|(damage, duration)| {
+                *damage as u64 * ((*duration).min(remaining) / SERVER_TICK + 1) as u64
+            })
+            .sum();
+        self.hit as u64 + ticks
+    }
+}
+
+/// Damage of a use with every buff in the table active, and with none
+#[derive(Debug, Clone, Default)]
+struct Buffed {
+    buffed: Vec<UseDamage>,
+    plain: Vec<UseDamage>,
+}
+
+impl Buffed {
+    fn push(&mut self, stats: &Stats, action: &Action, potency: u32, max_buffs: &Buffs) {
+        self.buffed
+            .push(UseDamage::new(stats, action, potency, max_buffs));
+        self.plain
+            .push(UseDamage::new(stats, action, potency, &Buffs::default()));
+    }
+
+    fn best(&self, remaining: u32) -> (u64, u64) {
+        let best = |uses: &[UseDamage]| {
+            uses.iter()
+                .map(|damage| damage.within(remaining))
+                .max()
+                .unwrap_or(0)
+        };
+        (best(&self.buffed), best(&self.plain))
+    }
+}
+
+/// Cooldown group whose uses are counted separately from the GCD
+#[derive(Debug, Clone, Default)]
+struct GroupBound {
+    /// Shortest recast of any owner of the group, in milliseconds
+    recast: u32,
+    /// Owners of the group that are GCDs, so each use takes a GCD slot
+    standalone: bool,
+    damage: Buffed,
+}
+
+/// Which actions can apply a status, to bound how often it can be up
+#[derive(Debug, Clone, Default)]
+struct StatusSource {
+    /// Some GCD without its own cooldown applies it, so it can be up all the time
+    unbounded: bool,
+    /// Recast timers of the cooldown groups whose owners apply it, with the stacks and duration
+    /// they give
+    groups: Vec<(usize, u32, u32)>,
+}
+
+/// Upper bound on the damage a player can still deal. It relaxes the rotation so that:
+///
+/// - every GCD gets the best potency that needs no Requiescat stack, and as many GCDs as there
+///   are Requiescat stacks to be had get the best enhanced potency instead
+/// - GCDs pressed while some buff could be up get every buff in the table at once, the rest get
+///   none, and oGCDs always get every buff
+/// - actions with their own cooldown are used the moment they come off it
+/// - damage over time runs until the end of the search whenever it is applied
+///
+/// Every GCD is counted at the best potency, while a real rotation spends most of them building
+/// up to it through the combo, Sword Oath and Divine Might. The gap grows with the horizon, and
+/// with it the states the search cannot prune: with the shipped table a 12 s horizon takes
+/// about 55000 nodes, 15 s about 2 million, and 20 s runs out of memory. Longer fights need a
+/// beam search.
+#[derive(Debug, Clone)]
+pub struct DamageBound {
+    global_recast: u32,
+    /// Shortest time any action keeps the player busy, so nothing is pressed later than this
+    /// before the end
+    min_cast: u32,
+    /// GCDs as `Player::apply_action` plays them without consuming a Requiescat stack
+    global: Buffed,
+    /// GCDs consuming a Requiescat stack for their enhanced potency
+    enhanced: Buffed,
+    /// Keyed by recast timer index
+    groups: BTreeMap<usize, GroupBound>,
+    sources: EnumMap<StatusName, StatusSource>,
+    /// Statuses that carry a damage modifier
+    buffs: Vec<StatusName>,
+}
+
+/// Every modifier any action in the table can apply, folded together
+fn max_buffs(actions_map: &EnumMap<ActionName, Action>, attack_type: AttackType) -> Buffs {
+    let mut buffs = Buffs::default();
+    for application in actions_map.values().flat_map(|action| &action.statuses) {
+        buffs.add(application.modifier, attack_type);
+    }
+    buffs
+}
+
+/// The potency `Player::apply_action` grants only while consuming a Requiescat stack
+fn requiescat_potency(action: &Action) -> Option<u32> {
+    match action.name {
+        ActionName::HolySpirit => Some(action.tertiary_potency),
+        ActionName::Confiteor
+        | ActionName::BladeOfFaith
+        | ActionName::BladeOfTruth
+        | ActionName::BladeOfValor => Some(action.secondary_potency),
+        _ => None,
+    }
+}
+
+/// Best potency of the action without a Requiescat stack
+fn free_potency(action: &Action) -> u32 {
+    let enhanced = match action.name {
+        ActionName::HolySpirit => action.secondary_potency,
+        _ if requiescat_potency(action).is_some() => 0,
+        _ => action.secondary_potency.max(action.tertiary_potency),
+    };
+    action.potency.max(action.combo_potency).max(enhanced)
+}
+
+/// Most uses a cooldown group can give within the window, recharging without pause
+fn group_uses(timer: &RecastTimer, recast: u32, window: u32) -> u32 {
+    if timer.charges == timer.max_charges {
+        timer.charges + window / recast
+    } else if timer.cooldown <= window {
+        timer.charges + 1 + (window - timer.cooldown) / recast
+    } else {
+        timer.charges
+    }
+}
+
+impl DamageBound {
+    pub fn new(actions_map: &EnumMap<ActionName, Action>, stats: &Stats) -> Self {
+        let haste = [
+            AttackType::Weaponskill,
+            AttackType::Spell,
+            AttackType::Ability,
+        ]
+        .map(|attack_type| max_buffs(actions_map, attack_type).haste)
+        .into_iter()
+        .max()
+        .unwrap();
+        let recast =
+            |action: &Action, base: u32| stats.recast(base, action.attack_type, haste).max(1);
+
+        let mut bound = DamageBound {
+            global_recast: u32::MAX,
+            min_cast: u32::MAX,
+            global: Buffed::default(),
+            enhanced: Buffed::default(),
+            groups: BTreeMap::new(),
+            sources: EnumMap::default(),
+            buffs: vec![],
+        };
+        let global_base = actions_map
+            .values()
+            .find(|action| action.cooldown_group == GLOBAL_COOLDOWN_GROUP)
+            .map(|action| action.recast);
+        for action in actions_map
+            .values()
+            .filter(|action| action.name != ActionName::None)
+        {
+            let max_buffs = max_buffs(actions_map, action.attack_type);
+            let cooldown_type = action.cooldown_type();
+            // Casts longer than the animation lock are scaled, and may be made instant
+            let cast = if action.cast > ANIMATION_LOCK {
+                ANIMATION_LOCK.min(recast(action, action.cast))
+            } else {
+                action.cast
+            };
+            bound.min_cast = bound.min_cast.min(cast);
+            if cooldown_type != CooldownType::OffGlobal {
+                if let Some(base) = global_base {
+                    bound.global_recast = bound.global_recast.min(recast(action, base));
+                }
+            }
+
+            let timer = action.timers[0].expect("every action but None owns a cooldown group");
+            for application in &action.statuses {
+                let source = &mut bound.sources[application.name];
+                if cooldown_type == CooldownType::Global {
+                    source.unbounded = true;
+                } else {
+                    source
+                        .groups
+                        .push((timer, application.stacks, application.duration));
+                }
+                if application.modifier != Modifier::None
+                    && !bound.buffs.contains(&application.name)
+                {
+                    bound.buffs.push(application.name);
+                }
+            }
+
+            if cooldown_type == CooldownType::Global {
+                bound
+                    .global
+                    .push(stats, action, free_potency(action), &max_buffs);
+                if let Some(potency) = requiescat_potency(action) {
+                    bound.enhanced.push(stats, action, potency, &max_buffs);
+                }
+                continue;
+            }
+            let group = bound.groups.entry(timer).or_insert(GroupBound {
+                recast: u32::MAX,
+                ..Default::default()
+            });
+            let group_recast = stats.group_recast(
+                action.cooldown_group,
+                action.recast,
+                action.attack_type,
+                haste,
+            );
+            group.recast = group.recast.min(group_recast.max(1));
+            group.standalone |= cooldown_type == CooldownType::GlobalStandalone;
+            let potency = free_potency(action).max(requiescat_potency(action).unwrap_or(0));
+            group.damage.push(stats, action, potency, &max_buffs);
+        }
+        bound
+    }
+
+    /// Uses of the cooldown group with the given recast timer that can be pressed within the
+    /// window
+    fn uses(&self, player: &Player, window: u32, timer: usize) -> u32 {
+        let recast = self
+            .groups
+            .get(&timer)
+            .map_or(u32::MAX, |bound| bound.recast);
+        group_uses(&player.recast_timers[timer], recast, window)
+    }
+
+    /// Most stacks of the status the player can have to spend within the window, `None` if a GCD
+    /// without its own cooldown applies it
+    fn stacks(&self, player: &Player, window: u32, name: StatusName) -> Option<u32> {
+        let source = &self.sources[name];
+        if source.unbounded {
+            return None;
+        }
+        let current = player.statuses[name];
+        let current = if current.is_active() {
+            current.stacks
+        } else {
+            0
+        };
+        let future: u32 = source
+            .groups
+            .iter()
+            .map(|(timer, stacks, _)| self.uses(player, window, *timer) * stacks)
+            .sum();
+        Some(current + future)
+    }
+
+    /// Most GCDs that can be pressed within the window while some buff is up, `None` if they all
+    /// can
+    fn buffed_slots(&self, player: &Player, window: u32) -> Option<u32> {
+        let mut slots = 0;
+        for name in &self.buffs {
+            let source = &self.sources[*name];
+            if source.unbounded {
+                return None;
+            }
+            let current = player.statuses[*name];
+            let (mut time, mut windows) = if current.is_active() {
+                (current.duration.min(window), 1)
+            } else {
+                (0, 0)
+            };
+            for (timer, _, duration) in &source.groups {
+                let uses = self.uses(player, window, *timer);
+                time += uses * duration;
+                windows += uses;
+            }
+            slots += time / self.global_recast + windows;
+        }
+        Some(slots)
+    }
+
+    /// Most damage the player could deal between now and `until`, with every step ending by then
+    pub fn remaining(&self, player: &Player, until: u32) -> u64 {
+        let remaining = until.saturating_sub(player.time);
+
+        let mut damage: u64 = player
+            .statuses
+            .values()
+            .filter(|status| status.is_active() && status.tick_damage > 0)
+            .map(|status| {
+                let ticks = status.duration.min(remaining) / SERVER_TICK + 1;
+                status.tick_damage as u64 * ticks as u64
+            })
+            .sum();
+        // The last step has to be pressed this long after now at the latest
+        let window = match remaining.checked_sub(self.min_cast) {
+            Some(window) => window,
+            None => return damage,
+        };
+
+        let slots = match player.recast_timer(GLOBAL_COOLDOWN_GROUP) {
+            Some(timer) if timer.wait_time() <= window => {
+                1 + (window - timer.wait_time()) / self.global_recast
+            }
+            _ => 0,
+        };
+        let buffed_slots = self
+            .buffed_slots(player, window)
+            .map_or(slots, |buffed| buffed.min(slots));
+        let (global_buffed, global_plain) = self.global.best(remaining);
+        damage += buffed_slots as u64 * global_buffed;
+        damage += (slots - buffed_slots) as u64 * global_plain;
+        // A GCD replaced by a stronger one gains at most the difference, buffed or not
+        let gain = |(buffed, plain): (u64, u64)| {
+            buffed
+                .saturating_sub(global_buffed)
+                .max(plain.saturating_sub(global_plain))
+        };
+
+        let enhanced = self
+            .stacks(player, window, StatusName::Requiescat)
+            .map_or(slots, |stacks| stacks.min(slots));
+        damage += enhanced as u64 * gain(self.enhanced.best(remaining));
+
+        for (timer, bound) in &self.groups {
+            let uses = self.uses(player, window, *timer);
+            if bound.standalone {
+                // Each use replaces one of the GCDs already counted above
+                damage += uses.min(slots) as u64 * gain(bound.damage.best(remaining));
+            } else {
+                damage += uses as u64 * bound.damage.best(remaining).0;
+            }
+        }
+        damage
+    }
+}
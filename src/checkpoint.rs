@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use enum_map::{Enum, EnumMap};
+
+use crate::{
+    constraints::{Constraints, Progress},
+    report,
+    search::{press_time, SearchOptions, SearchResult},
+    stats::Stats,
+    table, Action, ActionName, Player, StateKey, Step,
+};
+
+/// First bytes of every checkpoint file, ending in the format version
+const MAGIC: &[u8; 8] = b"ffxivck2";
+
+/// Where and how often a best-first or bound search saves its progress
+#[derive(Debug, Clone)]
+pub struct CheckpointOptions {
+    pub path: PathBuf,
+    pub interval: Duration,
+    /// Progress of an earlier run to continue from, see [`load`]
+    pub resume: Option<Snapshot>,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(PathBuf, io::Error),
+    /// A running search could not write its checkpoint or best rotation
+    Save(PathBuf, io::Error),
+    /// The checkpoint was saved by a search with other settings
+    Mismatch(PathBuf),
+    /// A step of the saved history cannot be taken with the current action table
+    Replay(PathBuf),
+    /// A step of the saved history breaks the constraints
+    Constraints(PathBuf),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            CheckpointError::Save(path, e) => {
+                write!(f, "cannot save checkpoint {}: {}", path.display(), e)
+            }
+            CheckpointError::Mismatch(path) => write!(
+                f,
+                "{}: checkpoint was saved with other search settings",
+                path.display()
+            ),
+            CheckpointError::Replay(path) => write!(
+                f,
+                "{}: checkpoint history does not replay with this action table",
+                path.display()
+            ),
+            CheckpointError::Constraints(path) => write!(
+                f,
+                "{}: checkpoint history breaks the constraints",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+/// Everything a search needs to continue where it was saved: the best value of every visited
+/// state with the step that reached it, the states still queued, and the best one so far. State
+/// keys and players are not saved, since replaying the history recovers them when loading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    settings: String,
+    pub(crate) damages: Vec<u32>,
+    pub(crate) history: Vec<(usize, Step)>,
+    /// Id of every visited state by its key
+    pub(crate) visited: HashMap<StateKey, usize>,
+    /// Queued states with their players and progress
+    pub(crate) frontier: Vec<(usize, Player, Progress)>,
+    pub(crate) best: Option<(u32, usize)>,
+    pub(crate) nodes: u64,
+    pub(crate) peak_heap: usize,
+}
+
+/// A [`Snapshot`] borrowing the search's tables, so saving does not copy them
+#[derive(Debug)]
+pub(crate) struct SnapshotRef<'a> {
+    pub settings: String,
+    pub damages: &'a [u32],
+    pub history: &'a [(usize, Step)],
+    pub frontier: Vec<usize>,
+    pub best: Option<(u32, usize)>,
+    pub nodes: u64,
+    pub peak_heap: usize,
+}
+
+/// FNV-1a hash of the bytes, which unlike `DefaultHasher` stays the same across Rust releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Everything that changes what a search finds, which a checkpoint has to agree on to be resumed.
+/// The action table goes in as a hash of its JSON.
+pub(crate) fn settings(
+    actions_map: &EnumMap<ActionName, Action>,
+    options: &SearchOptions,
+    constraints: &Constraints,
+    stats: &Stats,
+) -> String {
+    let actions = fnv1a(table::actions_to_json(actions_map).as_bytes());
+    format!(
+        "{:?} {:?} {:?} actions {:016x}",
+        options, constraints, stats, actions
+    )
+}
+
+/// File the best rotation so far is written to next to the checkpoint, e.g. `run.best.txt` for
+/// `run.ck`
+pub fn best_path(path: &Path) -> PathBuf {
+    path.with_extension("best.txt")
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_owned())
+}
+
+fn write_u32(out: &mut impl Write, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_u64(out: &mut impl Write, value: u64) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads `len` bytes without allocating them up front, so a corrupt length fails at the end of
+/// the file instead of exhausting memory
+fn read_bytes(input: &mut impl Read, len: u32) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    input.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() == len as usize {
+        Ok(bytes)
+    } else {
+        Err(io::ErrorKind::UnexpectedEof.into())
+    }
+}
+
+/// Ids are saved as `u32`, which covers far more states than fit in memory
+fn read_id(input: &mut impl Read, states: usize) -> io::Result<usize> {
+    let id = read_u32(input)? as usize;
+    if id < states {
+        Ok(id)
+    } else {
+        Err(invalid("state id out of range"))
+    }
+}
+
+/// A step as a kind and a value: 0 and the action, or 1 and the wait time
+fn write_step(out: &mut impl Write, step: &Step) -> io::Result<()> {
+    match step {
+        Step::Action(action_name) => {
+            write_u32(out, 0)?;
+            write_u32(out, action_name.into_usize() as u32)
+        }
+        Step::Wait(time) => {
+            write_u32(out, 1)?;
+            write_u32(out, *time)
+        }
+    }
+}
+
+fn read_step(input: &mut impl Read) -> io::Result<Step> {
+    match (read_u32(input)?, read_u32(input)?) {
+        (0, action) if (action as usize) < ActionName::LENGTH => {
+            Ok(Step::Action(ActionName::from_usize(action as usize)))
+        }
+        (1, time) => Ok(Step::Wait(time)),
+        _ => Err(invalid("unknown step")),
+    }
+}
+
+impl SnapshotRef<'_> {
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        write_u32(out, self.settings.len() as u32)?;
+        out.write_all(self.settings.as_bytes())?;
+        write_u64(out, self.nodes)?;
+        write_u64(out, self.peak_heap as u64)?;
+        // 0 if no state meets the constraints yet, else 1 and the value and id of the best one
+        let (found, value, id) = match self.best {
+            None => (0, 0, 0),
+            Some((value, id)) => (1, value, id as u32),
+        };
+        write_u32(out, found)?;
+        write_u32(out, value)?;
+        write_u32(out, id)?;
+        write_u32(out, self.damages.len() as u32)?;
+        for (damage, (parent, step)) in self.damages.iter().zip(self.history) {
+            write_u32(out, *damage)?;
+            write_u32(out, *parent as u32)?;
+            write_step(out, step)?;
+        }
+        write_u32(out, self.frontier.len() as u32)?;
+        for id in &self.frontier {
+            write_u32(out, *id as u32)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the snapshot to a temporary file first and renames it over the checkpoint, so a
+    /// run stopped while saving keeps the previous checkpoint. The best rotation goes next to
+    /// it, once there is one.
+    pub(crate) fn save(&self, path: &Path, best: Option<&SearchResult>) -> io::Result<()> {
+        let temporary = path.with_extension("tmp");
+        let mut file = io::BufWriter::new(fs::File::create(&temporary)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        fs::rename(&temporary, path)?;
+        match best {
+            Some(best) => fs::write(best_path(path), report::text(best)),
+            // Leave no best rotation of an earlier run behind
+            None => match fs::remove_file(best_path(path)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+impl Snapshot {
+    /// Reads a snapshot and the ids of its queued states, which [`Snapshot::replay`] turns into
+    /// the frontier
+    fn read(input: &mut impl Read) -> io::Result<(Snapshot, Vec<usize>)> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a checkpoint file"));
+        }
+        let len = read_u32(input)?;
+        let settings = read_bytes(input, len)?;
+        let settings =
+            String::from_utf8(settings).map_err(|_| invalid("settings are not UTF-8"))?;
+        let nodes = read_u64(input)?;
+        let peak_heap = read_u64(input)? as usize;
+        let best = match (read_u32(input)?, read_u32(input)?, read_u32(input)?) {
+            (0, _, _) => None,
+            (1, value, id) => Some((value, id as usize)),
+            _ => return Err(invalid("unknown best state")),
+        };
+        let states = read_u32(input)? as usize;
+        if states == 0 {
+            return Err(invalid("no states"));
+        }
+        // The counts come from the file, so the tables grow as states are read instead
+        let mut damages = vec![];
+        let mut history = vec![];
+        for _ in 0..states {
+            damages.push(read_u32(input)?);
+            history.push((read_id(input, states)?, read_step(input)?));
+        }
+        let frontier = (0..read_u32(input)?)
+            .map(|_| read_id(input, states))
+            .collect::<io::Result<_>>()?;
+        if best.is_some_and(|(_, id)| id >= states) {
+            return Err(invalid("state id out of range"));
+        }
+        let snapshot = Snapshot {
+            settings,
+            damages,
+            history,
+            visited: HashMap::new(),
+            frontier: vec![],
+            best,
+            nodes,
+            peak_heap,
+        };
+        Ok((snapshot, frontier))
+    }
+
+    /// Replays the history tree from a fresh player to recover the key of every visited state
+    /// and the player and progress of every queued one
+    fn replay(
+        &mut self,
+        actions_map: &EnumMap<ActionName, Action>,
+        stats: Stats,
+        constraints: &Constraints,
+        frontier: Vec<usize>,
+        path: &Path,
+    ) -> Result<(), CheckpointError> {
+        let states = self.damages.len();
+        // Children of every state in the history tree, grouped by parent. The root links to
+        // itself, so it is left out.
+        let mut starts = vec![0; states + 1];
+        for (parent, _) in &self.history[1..] {
+            starts[parent + 1] += 1;
+        }
+        for id in 0..states {
+            starts[id + 1] += starts[id];
+        }
+        let mut children = vec![0; states - 1];
+        let mut next = starts.clone();
+        for (id, (parent, _)) in self.history.iter().enumerate().skip(1) {
+            children[next[*parent]] = id;
+            next[*parent] += 1;
+        }
+        let mut queued = vec![false; states];
+        for id in frontier {
+            queued[id] = true;
+        }
+
+        let player = Player::new(stats, actions_map);
+        let mut stack = vec![(0, player, constraints.start())];
+        while let Some((id, player, progress)) = stack.pop() {
+            for &child in &children[starts[id]..starts[id + 1]] {
+                let step = self.history[child].1;
+                let time = press_time(actions_map, &player, &step);
+                let new_player = player
+                    .apply_step(&step, actions_map)
+                    .map_err(|_| CheckpointError::Replay(path.to_owned()))?;
+                let progress = constraints
+                    .advance(actions_map, &progress, &step, time, &new_player)
+                    .ok_or_else(|| CheckpointError::Constraints(path.to_owned()))?;
+                stack.push((child, new_player, progress));
+            }
+            self.visited
+                .insert(constraints.state_key(&player, &progress), id);
+            if queued[id] {
+                self.frontier.push((id, player, progress));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a checkpoint saved by a search with the same action table, options, constraints and
+/// stats, and replays its history
+pub fn load(
+    path: &Path,
+    actions_map: &EnumMap<ActionName, Action>,
+    options: &SearchOptions,
+    constraints: &Constraints,
+    stats: Stats,
+) -> Result<Snapshot, CheckpointError> {
+    let io_error = |e| CheckpointError::Io(path.to_owned(), e);
+    let file = fs::File::open(path).map_err(io_error)?;
+    let (mut snapshot, frontier) =
+        Snapshot::read(&mut io::BufReader::new(file)).map_err(io_error)?;
+    if snapshot.settings != settings(actions_map, options, constraints, &stats) {
+        return Err(CheckpointError::Mismatch(path.to_owned()));
+    }
+    snapshot.replay(actions_map, stats, constraints, frontier, path)?;
+    Ok(snapshot)
+}
@@ -0,0 +1,212 @@
+use std::str::FromStr;
+
+use enum_map::EnumMap;
+
+use crate::{sequence::parse_time, Action, ActionName, Player, StateKey, Step};
+
+/// An action together with a time window its press time is checked against
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ActionWindow {
+    pub action: ActionName,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl ActionWindow {
+    pub fn contains(&self, time: u32) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+fn parse_action(name: &str) -> Result<ActionName, String> {
+    ActionName::from_display_name(name).ok_or_else(|| format!("unknown action name `{}`", name))
+}
+
+impl FromStr for ActionWindow {
+    type Err = String;
+
+    /// Parses `ACTION@START-END`, e.g. `Intervene@40s-50s` or `Fight or Flight@5s-10s`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, window) = text
+            .split_once('@')
+            .ok_or_else(|| format!("expected ACTION@START-END, got `{}`", text))?;
+        let (start, end) = window
+            .split_once('-')
+            .ok_or_else(|| format!("expected ACTION@START-END, got `{}`", text))?;
+        let start = parse_time(start.trim()).map_err(|e| e.to_string())?;
+        let end = parse_time(end.trim()).map_err(|e| e.to_string())?;
+        if start >= end {
+            return Err(format!("window `{}` is empty", window));
+        }
+        Ok(ActionWindow {
+            action: parse_action(name.trim())?,
+            start,
+            end,
+        })
+    }
+}
+
+/// Most times an action may be used in the whole rotation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UseLimit {
+    pub action: ActionName,
+    pub uses: u32,
+}
+
+impl FromStr for UseLimit {
+    type Err = String;
+
+    /// Parses `ACTION=USES`, e.g. `Intervene=2`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, uses) = text
+            .split_once('=')
+            .ok_or_else(|| format!("expected ACTION=USES, got `{}`", text))?;
+        let uses = uses
+            .trim()
+            .parse()
+            .map_err(|_| format!("cannot parse use count `{}`", uses))?;
+        Ok(UseLimit {
+            action: parse_action(name.trim())?,
+            uses,
+        })
+    }
+}
+
+/// Rules every rotation of a search run has to follow. States that break one are pruned as soon
+/// as they are reached, and only states that meet all of them count as results.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Constraints {
+    /// Steps the rotation has to start with, in order
+    pub prefix: Vec<Step>,
+    /// The action may not be pressed inside the window
+    pub forbidden: Vec<ActionWindow>,
+    /// The action has to be pressed at least once inside the window
+    pub required: Vec<ActionWindow>,
+    pub max_uses: Vec<UseLimit>,
+}
+
+/// How far a rotation has come through the constraints, which is part of its search state
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Progress {
+    /// Steps of the prefix played so far
+    prefix: usize,
+    /// Uses of each limited action so far
+    uses: Vec<u32>,
+    /// Which required windows have been met
+    met: Vec<bool>,
+}
+
+impl Constraints {
+    pub fn is_empty(&self) -> bool {
+        self.prefix.is_empty()
+            && self.forbidden.is_empty()
+            && self.required.is_empty()
+            && self.max_uses.is_empty()
+    }
+
+    /// Progress of a rotation that has not started yet
+    pub fn start(&self) -> Progress {
+        Progress {
+            prefix: 0,
+            uses: vec![0; self.max_uses.len()],
+            met: vec![false; self.required.len()],
+        }
+    }
+
+    /// Steps the search may try next: the next step of the prefix until it is done, then `moves`
+    pub(crate) fn moves(
+        &self,
+        progress: &Progress,
+        moves: impl Iterator<Item = Step>,
+    ) -> Vec<Step> {
+        match self.prefix.get(progress.prefix) {
+            Some(step) => vec![*step],
+            None => moves.collect(),
+        }
+    }
+
+    /// Progress after pressing the step at `press_time` and reaching `player`, or `None` if the
+    /// step breaks a constraint or leaves a required window that can no longer be met
+    pub fn advance(
+        &self,
+        actions_map: &EnumMap<ActionName, Action>,
+        progress: &Progress,
+        step: &Step,
+        press_time: u32,
+        player: &Player,
+    ) -> Option<Progress> {
+        let mut next = progress.clone();
+        if let Some(prefix_step) = self.prefix.get(progress.prefix) {
+            if step != prefix_step {
+                return None;
+            }
+            next.prefix += 1;
+        }
+        if let Step::Action(action_name) = *step {
+            let forbidden = self
+                .forbidden
+                .iter()
+                .any(|window| window.action == action_name && window.contains(press_time));
+            if forbidden {
+                return None;
+            }
+            for (limit, uses) in self.max_uses.iter().zip(&mut next.uses) {
+                if limit.action == action_name {
+                    *uses += 1;
+                    if *uses > limit.uses {
+                        return None;
+                    }
+                }
+            }
+            for (window, met) in self.required.iter().zip(&mut next.met) {
+                if window.action == action_name && window.contains(press_time) {
+                    *met = true;
+                }
+            }
+        }
+        // The action cannot be pressed again before its cooldown is back
+        let missed = self.required.iter().zip(&next.met).any(|(window, met)| {
+            !met && player.time() + player.ready_in(&actions_map[window.action]) >= window.end
+        });
+        if missed {
+            None
+        } else {
+            Some(next)
+        }
+    }
+
+    /// Whether a rotation with this progress has played the whole prefix and met every required
+    /// window
+    pub fn is_complete(&self, progress: &Progress) -> bool {
+        progress.prefix == self.prefix.len() && progress.met.iter().all(|met| *met)
+    }
+
+    /// The player's state key, extended with the progress when there are constraints
+    pub(crate) fn state_key(&self, player: &Player, progress: &Progress) -> StateKey {
+        let key = player.state_key();
+        if self.is_empty() {
+            return key;
+        }
+        let mut extra = vec![progress.prefix as u32];
+        extra.extend(&progress.uses);
+        extra.extend(progress.met.iter().map(|met| *met as u32));
+        key.extended(&extra)
+    }
+
+    /// The progress `state_key` appended, from the values of the key after the player's
+    pub(crate) fn progress_from(&self, values: &mut impl Iterator<Item = u32>) -> Progress {
+        let mut progress = self.start();
+        if self.is_empty() {
+            return progress;
+        }
+        let mut next = || values.next().expect("state key ends early");
+        progress.prefix = next() as usize;
+        for uses in &mut progress.uses {
+            *uses = next();
+        }
+        for met in &mut progress.met {
+            *met = next() != 0;
+        }
+        progress
+    }
+}
@@ -0,0 +1,221 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use enum_map::EnumMap;
+
+use crate::{
+    beam::{self, BeamScore},
+    search::{record_steps, StepRecord},
+    stats::Stats,
+    Action, ActionName, Player, Step,
+};
+
+/// Periods of each beam rotation the candidate loops are cut from
+const BEAM_PERIODS: u32 = 5;
+
+/// Passes through a candidate loop before giving up on it settling into a cycle
+const MAX_PASSES: u32 = 16;
+
+/// Beam searches to run at most, each from where the best loop before it settles
+const MAX_ROUNDS: usize = 8;
+
+/// Settings of a steady-state cycle search
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CycleOptions {
+    /// Length of the loops to look for, in milliseconds, e.g. the 60 s of the longest recasts
+    pub period: u32,
+    /// Beam width of the searches whose rotations the loops are cut from
+    pub width: usize,
+    pub score: BeamScore,
+}
+
+impl Default for CycleOptions {
+    fn default() -> Self {
+        CycleOptions {
+            period: 60000,
+            width: 50,
+            score: BeamScore::Bound,
+        }
+    }
+}
+
+/// Best repeating rotation found by [`search`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleResult {
+    /// The search period times the number of period-long passes the loop takes to repeat, e.g.
+    /// 120000 for a loop that only comes back to its state every second 60 s pass
+    pub period: u32,
+    /// Steps from a fresh player up to the first pass through the loop
+    pub opener: Vec<StepRecord>,
+    /// Time at which the first pass starts
+    pub start: u32,
+    /// Steps of one pass through the loop, timed from its start
+    pub steps: Vec<StepRecord>,
+    /// Damage dealt by one pass
+    pub damage: u32,
+    /// Length of one pass, which can run past `period` by the last step's lock
+    pub time: u32,
+    /// Number of states expanded by the beam searches
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl CycleResult {
+    /// Mean damage per second of the loop, which the rotation keeps up for as long as it repeats
+    pub fn dps(&self) -> f64 {
+        self.damage as f64 / self.time as f64 * 1000.0
+    }
+}
+
+/// A loop found so far: `pass` played after `opener` returns to an equivalent state after
+/// `passes` period-long stretches, taking `time` and dealing `damage`
+#[derive(Debug, Clone)]
+struct Candidate {
+    opener: Vec<Step>,
+    pass: Vec<Step>,
+    passes: u32,
+    damage: u32,
+    time: u32,
+}
+
+impl Candidate {
+    fn beats(&self, other: &Option<Candidate>) -> bool {
+        other.as_ref().is_none_or(|other| {
+            self.damage as u64 * other.time as u64 > other.damage as u64 * self.time as u64
+        })
+    }
+}
+
+/// Plays `segment` over and over from the player until the state at the start of a pass repeats.
+/// The passes between the two visits then form a loop.
+fn settle(
+    actions_map: &EnumMap<ActionName, Action>,
+    mut player: Player,
+    mut opener: Vec<Step>,
+    segment: &[Step],
+) -> Option<Candidate> {
+    let mut seen = HashMap::new();
+    for passes in 0..MAX_PASSES {
+        let key = player.cycle_key();
+        if let Some(&(len, first, time, damage)) = seen.get(&key) {
+            let pass = opener.split_off(len);
+            return Some(Candidate {
+                opener,
+                pass,
+                passes: passes - first,
+                damage: player.damage - damage,
+                time: player.time - time,
+            });
+        }
+        seen.insert(key, (opener.len(), passes, player.time, player.damage));
+        for step in segment {
+            player = player.apply_step(step, actions_map).ok()?;
+            opener.push(*step);
+        }
+    }
+    None
+}
+
+/// Settles every period-long stretch of the steps, which are played from `start` after
+/// `opener`, and returns the loop with the most damage per time
+fn slice(
+    actions_map: &EnumMap<ActionName, Action>,
+    start: &Player,
+    opener: &[Step],
+    steps: &[Step],
+    period: u32,
+) -> Option<Candidate> {
+    let mut players = vec![start.clone()];
+    for step in steps {
+        let next = players
+            .last()
+            .unwrap()
+            .apply_step(step, actions_map)
+            .unwrap();
+        players.push(next);
+    }
+
+    let mut best: Option<Candidate> = None;
+    for (index, player) in players.iter().enumerate() {
+        let end = match players[index..]
+            .iter()
+            .position(|other| other.time >= player.time + period)
+        {
+            Some(length) => index + length,
+            None => break,
+        };
+        let prefix = [opener, &steps[..index]].concat();
+        if let Some(candidate) = settle(actions_map, player.clone(), prefix, &steps[index..end]) {
+            if candidate.beats(&best) {
+                best = Some(candidate);
+            }
+        }
+    }
+    best
+}
+
+/// Searches for the repeating rotation with the highest mean damage per second.
+///
+/// A loop is a sequence of steps that leads back to a state with the same
+/// [`Player::cycle_key`] it started from, so it can be repeated forever. Loops are cut from beam
+/// rotations over several periods: every period-long stretch is played over and over from where
+/// it starts until it settles into a loop, which it does when its cooldowns drift back into
+/// line, possibly only every few passes. The loop with the most damage per time wins.
+///
+/// The first beam starts from a fresh player, so its rotation opens with everything off
+/// cooldown. Each further beam starts from the state the best loop so far settles in, where
+/// cooldowns and resources are as they are in the middle of a fight, and its loops replace the
+/// best one as long as one of them has a higher mean damage per time. The loops are only as good
+/// as the beams let them be, so the result is the best loop found rather than a proven optimum.
+/// Returns `None` if no stretch settles into a loop.
+pub fn search(
+    actions_map: &EnumMap<ActionName, Action>,
+    stats: Stats,
+    options: &CycleOptions,
+) -> Option<CycleResult> {
+    let start = Instant::now();
+    let period = options.period;
+    let initial = Player::new(stats, actions_map);
+
+    let mut nodes = 0;
+    let mut best: Option<Candidate> = None;
+    let (mut from, mut opener) = (initial.clone(), vec![]);
+    for _ in 0..MAX_ROUNDS {
+        let horizon = from.time + BEAM_PERIODS * period;
+        let (steps, expanded) = beam::rotation_from(
+            actions_map,
+            from.clone(),
+            horizon,
+            options.width,
+            options.score,
+        );
+        nodes += expanded;
+        let candidate = match slice(actions_map, &from, &opener, &steps, period) {
+            Some(candidate) if candidate.beats(&best) => candidate,
+            _ => break,
+        };
+        (_, from) = record_steps(actions_map, initial.clone(), &candidate.opener);
+        opener = candidate.opener.clone();
+        best = Some(candidate);
+    }
+    let best = best?;
+
+    let (opener, anchor) = record_steps(actions_map, initial, &best.opener);
+    let (mut steps, end) = record_steps(actions_map, anchor.clone(), &best.pass);
+    debug_assert!(end.cycle_key() == anchor.cycle_key());
+    for record in &mut steps {
+        record.time -= anchor.time;
+    }
+    Some(CycleResult {
+        period: best.passes * period,
+        opener,
+        start: anchor.time,
+        steps,
+        damage: end.damage - anchor.damage,
+        time: end.time - anchor.time,
+        nodes,
+        elapsed: start.elapsed(),
+    })
+}
@@ -0,0 +1,899 @@
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::{
+    cmp,
+    fmt::{self, Debug},
+};
+
+use stats::Stats;
+use status::{Buffs, Modifier, StatusApplication, StatusEffect, StatusName};
+
+pub use alternatives::{Alternative, AlternativesResult};
+pub use beam::BeamScore;
+pub use checkpoint::CheckpointOptions;
+pub use constraints::Constraints;
+pub use cycle::{CycleOptions, CycleResult};
+pub use search::{Objective, SearchOptions, SearchResult, StepRecord, Strategy};
+pub use simulator::Simulator;
+
+pub mod alternatives;
+pub mod beam;
+pub mod bound;
+pub mod checkpoint;
+pub mod constraints;
+pub mod cycle;
+pub mod parallel;
+pub mod report;
+pub mod search;
+pub mod sequence;
+pub mod simulator;
+pub mod stats;
+pub mod status;
+pub mod table;
+pub mod xivapi;
+
+pub const ANIMATION_LOCK: u32 = 800;
+pub const GLOBAL_COOLDOWN: u32 = 2500;
+pub const GLOBAL_COOLDOWN_GROUP: u32 = 58;
+pub const SERVER_TICK: u32 = 3000;
+pub const COMBO_DURATION: u32 = 30000;
+
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Enum, Serialize, Deserialize,
+)]
+pub enum ActionName {
+    None,
+    FastBlade,
+    FightOrFlight,
+    RiotBlade,
+    CircleOfScorn,
+    GoringBlade,
+    RoyalAuthority,
+    HolySpirit,
+    Requiescat,
+    Intervene,
+    Atonement,
+    Confiteor,
+    Expiacion,
+    BladeOfFaith,
+    BladeOfTruth,
+    BladeOfValor,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum CooldownType {
+    Global,
+    GlobalStandalone,
+    OffGlobal,
+}
+
+/// A single entry of a rotation: press an action, or deliberately do nothing for some time
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Step {
+    Action(ActionName),
+    Wait(u32),
+}
+
+impl Debug for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Action(action_name) => write!(f, "{:?}", action_name),
+            Step::Wait(time) => write!(f, "Wait({}ms)", time),
+        }
+    }
+}
+
+impl fmt::Display for ActionName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = format!("{:?}", self);
+        let mut words = vec![];
+        let mut start = 0;
+        for (index, c) in name.char_indices().skip(1) {
+            if c.is_ascii_uppercase() {
+                words.push(&name[start..index]);
+                start = index;
+            }
+        }
+        words.push(&name[start..]);
+        let words: Vec<&str> = words
+            .into_iter()
+            .map(|word| match word {
+                "Of" => "of",
+                "Or" => "or",
+                word => word,
+            })
+            .collect();
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum AttackType {
+    Weaponskill,
+    Spell,
+    Ability,
+}
+
+/// What using an action does to the current combo
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Default, Serialize, Deserialize)]
+pub enum ComboEffect {
+    /// Leaves the combo untouched
+    #[default]
+    Keep,
+    /// Becomes the new combo action, or breaks the combo if `combo_from` was not met
+    Continue,
+    /// Ends the combo
+    Break,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Combo {
+    action: ActionName,
+    remaining: u32,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            action: ActionName::None,
+            remaining: 0,
+        }
+    }
+}
+
+impl Combo {
+    /// The action the next combo step has to follow, `None` when no combo is running
+    pub fn action(&self) -> ActionName {
+        self.action
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    pub fn start(&mut self, action: ActionName) {
+        self.action = action;
+        self.remaining = COMBO_DURATION;
+    }
+
+    pub fn tick(&mut self, time: u32) {
+        self.remaining = self.remaining.saturating_sub(time);
+        if self.remaining == 0 {
+            self.action = ActionName::None;
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Action {
+    name: ActionName,
+    attack_type: AttackType,
+    cooldown_group: u32,
+    additional_cooldown_group: u32,
+    cast: u32,
+    recast: u32,
+    mp_cost: u32,
+    mp_restore: u32,
+    potency: u32,
+    combo_potency: u32,
+    secondary_potency: u32,
+    tertiary_potency: u32,
+    max_charges: u32,
+    statuses: Vec<StatusApplication>,
+    combo_from: Option<ActionName>,
+    combo_required: bool,
+    combo_effect: ComboEffect,
+    /// Indices of the recast timers of the cooldown groups in `Player::recast_timers`, assigned
+    /// when the table is loaded
+    timers: [Option<usize>; 2],
+}
+
+impl Action {
+    pub fn name(&self) -> ActionName {
+        self.name
+    }
+
+    pub fn attack_type(&self) -> AttackType {
+        self.attack_type
+    }
+
+    pub fn cooldown_groups(&self) -> impl Iterator<Item = u32> {
+        [self.cooldown_group, self.additional_cooldown_group]
+            .into_iter()
+            .filter(|group| *group != 0)
+    }
+
+    fn timers(&self) -> impl Iterator<Item = usize> {
+        self.timers.into_iter().flatten()
+    }
+
+    pub fn cooldown_type(&self) -> CooldownType {
+        if self.cooldown_group == GLOBAL_COOLDOWN_GROUP {
+            CooldownType::Global
+        } else if self.additional_cooldown_group == GLOBAL_COOLDOWN_GROUP {
+            CooldownType::GlobalStandalone
+        } else {
+            CooldownType::OffGlobal
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
+pub struct RecastTimer {
+    group: u32,
+    recast: u32,
+    max_charges: u32,
+    cooldown: u32,
+    charges: u32,
+}
+
+impl Debug for RecastTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cd={}, charges={}/{}",
+            self.cooldown, self.charges, self.max_charges
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Player {
+    time: u32,
+    mp: u32,
+    damage: u32,
+    stats: Stats,
+    /// One timer per cooldown group some action owns, indexed as `Action::timers`
+    recast_timers: Box<[RecastTimer]>,
+    combo: Combo,
+    statuses: EnumMap<StatusName, StatusEffect>,
+}
+
+// impl Player {
+//     fn dps(&self) -> f64 {
+//         (self.damage as f64) / (self.time as f64)
+//     }
+// }
+
+/// Exact encoding of the parts of a player that change during a fight, so two players share a
+/// key only if they behave the same from here on. Values fixed by the action table, like recasts
+/// and status modifiers, are left out. Searches keep one key per visited state, so the values
+/// are stored as LEB128 bytes, which takes one byte for the many zeros and small counts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateKey(Box<[u8]>);
+
+impl StateKey {
+    fn new(values: impl IntoIterator<Item = u32>, capacity: usize) -> StateKey {
+        let mut key = Vec::with_capacity(capacity);
+        for value in values {
+            StateKey::push(&mut key, value);
+        }
+        StateKey(key.into_boxed_slice())
+    }
+
+    /// Appends seven bits at a time, low bits first, with the high bit set on all but the last
+    fn push(key: &mut Vec<u8>, mut value: u32) {
+        while value >= 0x80 {
+            key.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        key.push(value as u8);
+    }
+
+    /// The values the key was built from, in order
+    pub(crate) fn values(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut bytes = self.0.iter();
+        std::iter::from_fn(move || {
+            let mut value = 0;
+            let mut shift = 0;
+            loop {
+                let byte = *bytes.next()?;
+                value |= ((byte & 0x7f) as u32) << shift;
+                if byte < 0x80 {
+                    return Some(value);
+                }
+                shift += 7;
+            }
+        })
+    }
+
+    /// The key with more values appended, for state a search tracks next to the player
+    pub(crate) fn extended(self, extra: &[u32]) -> StateKey {
+        let mut key = self.0.into_vec();
+        for value in extra {
+            StateKey::push(&mut key, *value);
+        }
+        StateKey(key.into_boxed_slice())
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Player {
+            time: 0,
+            mp: 10000,
+            damage: 0,
+            stats: Stats::default(),
+            recast_timers: Box::new([]),
+            combo: Combo::default(),
+            statuses: EnumMap::default(),
+        }
+    }
+}
+
+impl PartialOrd for Player {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Player {
+    /// Orders by damage per time, compared exactly by cross-multiplying in `u64`. Ties go to the
+    /// later state, then to more damage, then to more MP, so a time-0 state (whose rate is
+    /// undefined) only ties with states that share its time. Players still tied are ordered by
+    /// the rest of their fields, so only equal players compare equal.
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        let rate = self.damage as u64 * other.time as u64;
+        let other_rate = other.damage as u64 * self.time as u64;
+        rate.cmp(&other_rate)
+            .then(self.time.cmp(&other.time))
+            .then(self.damage.cmp(&other.damage))
+            .then(self.mp.cmp(&other.mp))
+            .then_with(|| self.combo.cmp(&other.combo))
+            .then_with(|| self.recast_timers.cmp(&other.recast_timers))
+            .then_with(|| self.statuses.cmp(&other.statuses))
+            .then_with(|| self.stats.cmp(&other.stats))
+    }
+}
+
+impl RecastTimer {
+    pub fn new(group: u32, recast: u32, max_charges: u32) -> Self {
+        RecastTimer {
+            group,
+            recast,
+            max_charges,
+            cooldown: 0,
+            charges: max_charges,
+        }
+    }
+
+    pub fn group(&self) -> u32 {
+        self.group
+    }
+
+    pub fn cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    pub fn charges(&self) -> u32 {
+        self.charges
+    }
+
+    pub fn max_charges(&self) -> u32 {
+        self.max_charges
+    }
+
+    pub fn wait_time(&self) -> u32 {
+        if self.charges == 0 {
+            self.cooldown
+        } else {
+            0
+        }
+    }
+
+    pub fn consume(&mut self, recast: u32) {
+        if self.charges == self.max_charges {
+            self.cooldown = recast;
+        }
+        self.charges -= 1;
+    }
+
+    pub fn tick(&mut self, time: u32) {
+        let mut time = time;
+        while self.charges < self.max_charges && time > 0 {
+            if self.cooldown > time {
+                self.cooldown -= time;
+                time = 0;
+            } else {
+                time -= self.cooldown;
+                self.charges += 1;
+                self.cooldown = if self.charges == self.max_charges {
+                    0
+                } else {
+                    self.recast
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ApplyErrorKind {
+    ComboRequired {
+        required: ActionName,
+        current: ActionName,
+    },
+    StatusRequired(StatusName),
+    OnCooldown {
+        remaining: u32,
+        charges: u32,
+        max_charges: u32,
+    },
+    MpNotEnough {
+        required: u32,
+        available: u32,
+    },
+    NoneAction,
+}
+
+#[derive(Debug)]
+pub struct ActionApplyError {
+    pub action: ActionName,
+    pub time: u32,
+    pub kind: ApplyErrorKind,
+}
+
+impl fmt::Display for ApplyErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApplyErrorKind::ComboRequired { required, current } => write!(
+                f,
+                "requires {} combo, current combo is {}",
+                required, current
+            ),
+            ApplyErrorKind::StatusRequired(status) => {
+                write!(f, "requires {:?}, which is not active", status)
+            }
+            ApplyErrorKind::OnCooldown {
+                remaining,
+                charges,
+                max_charges,
+            } => write!(
+                f,
+                "on cooldown for {} ms, charges {}/{}",
+                remaining, charges, max_charges
+            ),
+            ApplyErrorKind::MpNotEnough {
+                required,
+                available,
+            } => write!(f, "needs {} MP, only {} available", required, available),
+            ApplyErrorKind::NoneAction => write!(f, "is not an action"),
+        }
+    }
+}
+
+impl fmt::Display for ActionApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {:.1}s: {} {}",
+            self.time as f64 / 1000.0,
+            self.action,
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for ActionApplyError {}
+
+impl Player {
+    pub fn new(stats: Stats, actions_map: &EnumMap<ActionName, Action>) -> Self {
+        let mut player = Player {
+            stats,
+            ..Default::default()
+        };
+        player.assign_actions(actions_map);
+        player
+    }
+
+    pub fn time(&self) -> u32 {
+        self.time
+    }
+
+    pub fn mp(&self) -> u32 {
+        self.mp
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn combo(&self) -> Combo {
+        self.combo
+    }
+
+    pub fn status(&self, name: StatusName) -> &StatusEffect {
+        &self.statuses[name]
+    }
+
+    pub fn recast_timer(&self, group: u32) -> Option<&RecastTimer> {
+        self.recast_timers.iter().find(|timer| timer.group == group)
+    }
+
+    pub fn state_key(&self) -> StateKey {
+        self.key_at(self.time)
+    }
+
+    /// The state key with the time replaced by how far it is into the current server tick. Two
+    /// players with the same cycle key are in the same state up to a shift by whole ticks, so
+    /// whatever one can do the other can repeat with the same damage.
+    pub fn cycle_key(&self) -> StateKey {
+        self.key_at(self.time % SERVER_TICK)
+    }
+
+    fn key_at(&self, time: u32) -> StateKey {
+        let values = [
+            time,
+            self.mp,
+            self.combo.action as u32,
+            self.combo.remaining,
+        ]
+        .into_iter()
+        .chain(
+            self.recast_timers
+                .iter()
+                .flat_map(|timer| [timer.cooldown, timer.charges]),
+        )
+        .chain(self.statuses.values().flat_map(StatusEffect::key));
+        StateKey::new(
+            values,
+            4 + self.recast_timers.len() * 2 + self.statuses.len() * StatusEffect::KEY_LEN,
+        )
+    }
+
+    /// Rebuilds the player whose [`Player::state_key`] gave the values, from what the key leaves
+    /// out: the damage dealt so far, the stats and the action table. Values the key was extended
+    /// with are left in the iterator.
+    pub(crate) fn from_key(
+        values: &mut impl Iterator<Item = u32>,
+        damage: u32,
+        stats: Stats,
+        actions_map: &EnumMap<ActionName, Action>,
+    ) -> Player {
+        let mut player = Player::new(stats, actions_map);
+        let mut next = || values.next().expect("state key ends early");
+        player.time = next();
+        player.mp = next();
+        player.damage = damage;
+        player.combo = Combo {
+            action: ActionName::from_usize(next() as usize),
+            remaining: next(),
+        };
+        for timer in player.recast_timers.iter_mut() {
+            timer.cooldown = next();
+            timer.charges = next();
+        }
+        for (name, status) in player.statuses.iter_mut() {
+            status.duration = next();
+            status.stacks = next();
+            status.tick_damage = next();
+            if status.is_active() {
+                status.modifier = actions_map
+                    .values()
+                    .flat_map(|action| &action.statuses)
+                    .find(|application| application.name == name)
+                    .map_or(Modifier::None, |application| application.modifier);
+            }
+        }
+        player
+    }
+
+    pub fn assign_actions(&mut self, actions_map: &EnumMap<ActionName, Action>) {
+        self.statuses = EnumMap::default();
+        // An action's recast and charges define the timer of its primary group. Additional
+        // groups only lock out when some action owns them, e.g. the GCD group for Goring Blade.
+        let mut timers = vec![None; table::timer_count(actions_map)];
+        for action in actions_map.values() {
+            if let Some(index) = action.timers[0] {
+                let timer =
+                    RecastTimer::new(action.cooldown_group, action.recast, action.max_charges);
+                timers[index] = Some(timer);
+            }
+        }
+        self.recast_timers = timers.into_iter().map(Option::unwrap).collect();
+    }
+
+    pub fn recover_mp(&mut self, mp: u32) {
+        self.mp += mp;
+        if self.mp > 10000 {
+            self.mp = 10000
+        };
+    }
+
+    /// When each server tick over the next `time` lands and the damage over time it deals
+    fn server_ticks(&self, time: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let first = self.time / SERVER_TICK + 1;
+        let last = (self.time + time) / SERVER_TICK;
+        (first..=last).map(move |n| {
+            let elapsed = n * SERVER_TICK - self.time;
+            let tick_damage = self
+                .statuses
+                .values()
+                .filter(|status| status.is_active() && status.duration >= elapsed)
+                .map(|status| status.tick_damage)
+                .sum();
+            (n * SERVER_TICK, tick_damage)
+        })
+    }
+
+    pub fn tick(&mut self, time: u32) {
+        let server_ticks = self.server_ticks(time).count() as u32;
+        let tick_damage: u32 = self.server_ticks(time).map(|(_, damage)| damage).sum();
+        self.recover_mp(server_ticks * 200);
+        self.damage += tick_damage;
+        self.time += time;
+        for timer in self.recast_timers.iter_mut() {
+            timer.tick(time);
+        }
+        for status in self.statuses.values_mut() {
+            status.tick(time);
+        }
+        self.combo.tick(time);
+    }
+
+    pub fn buffs(&self, attack_type: AttackType) -> Buffs {
+        let mut buffs = Buffs::default();
+        for status in self.statuses.values().filter(|status| status.is_active()) {
+            buffs.add(status.modifier, attack_type);
+        }
+        buffs
+    }
+
+    pub fn recast(&self, base: u32, attack_type: AttackType) -> u32 {
+        let haste = self.buffs(attack_type).haste;
+        self.stats.recast(base, attack_type, haste)
+    }
+
+    pub fn hit(&mut self, potency: u32, attack_type: AttackType) {
+        let buffs = self.buffs(attack_type);
+        self.damage += self.stats.expected_damage(potency, &buffs);
+    }
+
+    /// Time until the next recast timer regains a charge, if any is cooling down
+    pub fn next_ready_time(&self) -> Option<u32> {
+        self.recast_timers
+            .iter()
+            .map(|timer| timer.cooldown)
+            .filter(|cooldown| *cooldown > 0)
+            .min()
+    }
+
+    fn error(&self, action: &Action, kind: ApplyErrorKind) -> ActionApplyError {
+        ActionApplyError {
+            action: action.name,
+            time: self.time,
+            kind,
+        }
+    }
+
+    /// Time until every cooldown group of the action has a charge available
+    pub fn ready_in(&self, action: &Action) -> u32 {
+        action
+            .timers()
+            .map(|index| &self.recast_timers[index])
+            .map(RecastTimer::wait_time)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn apply_step(
+        &self,
+        step: &Step,
+        actions_map: &EnumMap<ActionName, Action>,
+    ) -> Result<Self, ActionApplyError> {
+        match step {
+            Step::Action(action_name) => self.apply_action(action_name, actions_map),
+            Step::Wait(time) => {
+                let mut ret = self.clone();
+                ret.tick(*time);
+                Ok(ret)
+            }
+        }
+    }
+
+    pub fn apply_action(
+        &self,
+        action_name: &ActionName,
+        actions_map: &EnumMap<ActionName, Action>,
+    ) -> Result<Self, ActionApplyError> {
+        let (mut ret, cast) = self.press(action_name, actions_map)?;
+        ret.tick(cast);
+        Ok(ret)
+    }
+
+    /// Damage the step deals that lands inside [start, end): ticks count when they land and the
+    /// hit when the action is pressed
+    pub fn damage_within(
+        &self,
+        step: &Step,
+        actions_map: &EnumMap<ActionName, Action>,
+        start: u32,
+        end: u32,
+    ) -> u32 {
+        let within = |time: u32| start <= time && time < end;
+        let ticks = |player: &Player, time: u32| -> u32 {
+            player
+                .server_ticks(time)
+                .filter(|(landed, _)| within(*landed))
+                .map(|(_, damage)| damage)
+                .sum()
+        };
+        let action_name = match step {
+            Step::Action(action_name) => action_name,
+            Step::Wait(time) => return ticks(self, *time),
+        };
+        let wait_time = self.ready_in(&actions_map[*action_name]);
+        let (pressed, cast) = match self.press(action_name, actions_map) {
+            Ok(pressed) => pressed,
+            Err(_) => return 0,
+        };
+        let mut waited = self.clone();
+        waited.tick(wait_time);
+        let hit = if within(pressed.time) {
+            pressed.damage - waited.damage
+        } else {
+            0
+        };
+        ticks(self, wait_time) + hit + ticks(&pressed, cast)
+    }
+
+    /// The player the moment the action lands, before its cast or animation lock, and how long
+    /// that lock is
+    fn press(
+        &self,
+        action_name: &ActionName,
+        actions_map: &EnumMap<ActionName, Action>,
+    ) -> Result<(Self, u32), ActionApplyError> {
+        let action = &actions_map[*action_name];
+        let mut ret = self.clone();
+
+        let wait_time = ret.ready_in(action);
+
+        if wait_time > ret.recast(GLOBAL_COOLDOWN, AttackType::Weaponskill) {
+            let timer = action
+                .timers()
+                .map(|index| &ret.recast_timers[index])
+                .max_by_key(|timer| timer.wait_time())
+                .unwrap();
+            return Err(self.error(
+                action,
+                ApplyErrorKind::OnCooldown {
+                    remaining: timer.cooldown,
+                    charges: timer.charges,
+                    max_charges: timer.max_charges,
+                },
+            ));
+        }
+
+        ret.tick(wait_time);
+
+        // while ret.mp < action.mp_cost {
+        //     ret.tick(((ret.time / SERVER_TICK) + 1) * SERVER_TICK - ret.time);
+        // }
+        if ret.mp < action.mp_cost {
+            return Err(self.error(
+                action,
+                ApplyErrorKind::MpNotEnough {
+                    required: action.mp_cost,
+                    available: ret.mp,
+                },
+            ));
+        }
+        ret.mp -= action.mp_cost;
+        ret.recover_mp(action.mp_restore);
+
+        let haste = ret.buffs(action.attack_type).haste;
+        for index in action.timers() {
+            let timer = &mut ret.recast_timers[index];
+            let base = if Some(index) == action.timers[0] {
+                action.recast
+            } else {
+                timer.recast
+            };
+            let recast = ret
+                .stats
+                .group_recast(timer.group, base, action.attack_type, haste);
+            timer.consume(recast);
+        }
+
+        let combo = action.combo_from.is_some() && action.combo_from == Some(ret.combo.action);
+        if let (true, false, Some(required)) = (action.combo_required, combo, action.combo_from) {
+            return Err(self.error(
+                action,
+                ApplyErrorKind::ComboRequired {
+                    required,
+                    current: ret.combo.action,
+                },
+            ));
+        }
+        match action.combo_effect {
+            ComboEffect::Keep => {}
+            ComboEffect::Continue if combo || action.combo_from.is_none() => {
+                ret.combo.start(action.name);
+            }
+            ComboEffect::Continue | ComboEffect::Break => {
+                ret.combo = Combo::default();
+            }
+        }
+
+        // Actions that only need their combo to be usable, like the Blades, have no combo potency
+        let mut potency = if combo && action.combo_potency > 0 {
+            action.combo_potency
+        } else {
+            action.potency
+        };
+        let mut cast = if action.cast > ANIMATION_LOCK {
+            ret.recast(action.cast, action.attack_type)
+        } else {
+            action.cast
+        };
+
+        match action.name {
+            ActionName::None => {
+                return Err(self.error(action, ApplyErrorKind::NoneAction));
+            }
+            ActionName::FastBlade => {}
+            ActionName::FightOrFlight => {}
+            ActionName::RiotBlade => {}
+            ActionName::CircleOfScorn => {}
+            ActionName::GoringBlade => {}
+            ActionName::RoyalAuthority => {}
+            ActionName::HolySpirit => {
+                if ret.statuses[StatusName::DivineMight].consume() {
+                    potency = action.secondary_potency;
+                    cast = ANIMATION_LOCK;
+                } else if ret.statuses[StatusName::Requiescat].consume() {
+                    potency = action.tertiary_potency;
+                    cast = ANIMATION_LOCK;
+                }
+            }
+            ActionName::Requiescat => {}
+            ActionName::Intervene => {}
+            ActionName::Atonement => {
+                if !ret.statuses[StatusName::SwordOath].consume() {
+                    return Err(self.error(
+                        action,
+                        ApplyErrorKind::StatusRequired(StatusName::SwordOath),
+                    ));
+                }
+            }
+            ActionName::Confiteor => {
+                if !ret.statuses[StatusName::ConfiteorReady].consume() {
+                    return Err(self.error(
+                        action,
+                        ApplyErrorKind::StatusRequired(StatusName::ConfiteorReady),
+                    ));
+                }
+                if ret.statuses[StatusName::Requiescat].consume() {
+                    potency = action.secondary_potency;
+                }
+            }
+            ActionName::Expiacion => {}
+            ActionName::BladeOfFaith | ActionName::BladeOfTruth | ActionName::BladeOfValor => {
+                if ret.statuses[StatusName::Requiescat].consume() {
+                    potency = action.secondary_potency;
+                }
+            }
+        };
+
+        ret.hit(potency, action.attack_type);
+        let buffs = ret.buffs(action.attack_type);
+        for application in &action.statuses {
+            if combo || !application.combo {
+                // Damage over time snapshots the buffs active when it is applied
+                let tick_damage = if application.potency > 0 {
+                    ret.stats.expected_damage(application.potency, &buffs)
+                } else {
+                    0
+                };
+                ret.statuses[application.name].apply(application, tick_damage);
+            }
+        }
+        Ok((ret, cast))
+    }
+}
@@ -1,677 +1,262 @@
-use enum_map::{enum_map, Enum, EnumMap};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-use std::{cmp, fmt::Debug};
-
-use search::search;
-
-mod search;
-mod sequence;
-
-// const DAMANGE_PER_100_POTENCY: u32 = 2706;
-const ANIMATION_LOCK: u32 = 800;
-const GLOBAL_COOLDOWN: u32 = 2500;
-
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
-}
-
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum BasicCombo {
-    None,
-    FastBlade,
-    RiotBlade,
-}
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum BladeCombo {
-    None,
-    Confiteor,
-    BladeOfFaith,
-    BladeOfTruth,
-}
-
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum DivineMight {
-    None,
-    Ready,
-}
-
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Confiteor {
-    None,
-    Ready,
-}
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Enum)]
-pub enum ActionName {
-    None,
-    FastBlade,
-    FightOrFlight,
-    RiotBlade,
-    CircleOfScorn,
-    GoringBlade,
-    RoyalAuthority,
-    HolySpirit,
-    Requiescat,
-    Intervene,
-    Atonement,
-    Confiteor,
-    Expiacion,
-    BladeOfFaith,
-    BladeOfTruth,
-    BladeOfValor,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub enum CooldownType {
-    Global,
-    GlobalStandalone,
-    OffGlobal,
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use ffxiv_rotation::{
+    constraints::{ActionWindow, UseLimit},
+    report, search, sequence, stats,
+    stats::Stats,
+    table, xivapi, BeamScore, CheckpointOptions, Constraints, CycleOptions, Objective,
+    SearchOptions, Simulator, Step, Strategy,
+};
+
+#[derive(Parser)]
+#[command(about = "Paladin rotation simulator and optimizer")]
+struct Cli {
+    /// Action table to load
+    #[arg(long, default_value = "actions.json")]
+    actions: PathBuf,
+    /// Character stats to load
+    #[arg(long, default_value = "stats.json")]
+    stats: PathBuf,
+    /// Override the skill speed from the stats file
+    #[arg(long)]
+    skill_speed: Option<u32>,
+    /// Override the spell speed from the stats file
+    #[arg(long)]
+    spell_speed: Option<u32>,
+    #[command(subcommand)]
+    command: Command,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct Action {
-    name: ActionName,
-    cooldown_type: CooldownType,
-    cast: u32,
-    recast: u32,
-    max_duration: u32,
-    max_count: u32,
-    mp_cost: u32,
-    mp_restore: u32,
-    potency: u32,
-    secondary_potency: u32,
-    tertiary_potency: u32,
-    max_charges: u32,
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Csv,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Hash)]
-pub struct ActionStatus {
-    name: ActionName,
-    cooldown: u32,
-    duration: u32,
-    charges: u32,
-    count: u32,
+#[derive(Subcommand)]
+enum Command {
+    /// Search for the highest damage rotation
+    Search {
+        /// Fight length to optimize for, in milliseconds
+        #[arg(long, default_value_t = search::MAX_TIME)]
+        horizon: u32,
+        /// What to optimize: damage, dps, burst:START-END (e.g. burst:20s-40s) or
+        /// time-to:DAMAGE
+        #[arg(long, default_value = "damage")]
+        objective: Objective,
+        /// How to explore: best-first visits every state, bound prunes with an upper bound,
+        /// beam:WIDTH[:SCORE] keeps the WIDTH best states per GCD by value, rate or bound,
+        /// parallel[:THREADS] visits every state on several threads
+        #[arg(long, default_value = "best-first")]
+        strategy: Strategy,
+        /// Rotation file the rotation has to start with, without pinned times
+        #[arg(long)]
+        prefix: Option<PathBuf>,
+        /// Never press the action inside the window, e.g. Intervene@40s-50s
+        #[arg(long)]
+        forbid: Vec<ActionWindow>,
+        /// Press the action at least once inside the window, e.g. "Fight or Flight@5s-10s"
+        #[arg(long)]
+        require: Vec<ActionWindow>,
+        /// Press the action at most this many times, e.g. Intervene=2
+        #[arg(long)]
+        max_uses: Vec<UseLimit>,
+        /// Print the best TOP distinct rotations and where they diverge from the best one,
+        /// visiting every state like best-first
+        #[arg(long)]
+        top: Option<usize>,
+        /// Save the search's progress to this file as it goes, and the best rotation so far next
+        /// to it, e.g. run.best.txt for run.ck
+        #[arg(long, conflicts_with = "top")]
+        checkpoint: Option<PathBuf>,
+        /// Seconds between checkpoints, at least 1
+        #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+        checkpoint_every: u64,
+        /// Continue from the checkpoint file instead of starting over
+        #[arg(long, requires = "checkpoint")]
+        resume: bool,
+        /// How to print the best rotation
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Search for the repeating rotation with the highest DPS
+    Cycle {
+        /// Length of the loops to look for, in milliseconds
+        #[arg(long, default_value_t = 60000)]
+        period: u32,
+        /// Beam width of the underlying searches
+        #[arg(long, default_value_t = 50)]
+        width: usize,
+        /// How the beam ranks states: value, rate or bound
+        #[arg(long, default_value = "bound")]
+        score: BeamScore,
+        /// How to print the loop
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Replay a rotation file and print every step
+    Play { sequence: PathBuf },
+    /// Check an action table for errors
+    Validate { table: Option<PathBuf> },
+    /// Merge timings from an xivapi snapshot into the action table and print it
+    Import {
+        #[arg(long, default_value = "xivapi.json")]
+        snapshot: PathBuf,
+    },
 }
 
-impl Default for ActionStatus {
-    fn default() -> Self {
-        ActionStatus {
-            name: ActionName::None,
-            cooldown: 0,
-            duration: 0,
-            charges: 0,
-            count: 0,
+impl Cli {
+    fn load_stats(&self) -> Stats {
+        let mut stats = or_exit(stats::load_stats(&self.stats));
+        if let Some(skill_speed) = self.skill_speed {
+            stats.skill_speed = skill_speed;
         }
-    }
-}
-
-impl Debug for ActionStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "cd={}, charges={}, duration={}, count={}",
-            self.cooldown, self.charges, self.duration, self.count
-        )
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Player {
-    time: u32,
-    mp: u32,
-    damage: u32,
-    global_cooldown: u32,
-    basic_combo: BasicCombo,
-    blade_combo: BladeCombo,
-    divine_might: DivineMight,
-    confiteor: Confiteor,
-    action_status: EnumMap<ActionName, ActionStatus>,
-}
-
-// impl Player {
-//     fn dps(&self) -> f64 {
-//         (self.damage as f64) / (self.time as f64)
-//     }
-// }
-
-impl Hash for Player {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.time.hash(state);
-        self.mp.hash(state);
-        self.global_cooldown.hash(state);
-        self.basic_combo.hash(state);
-        self.blade_combo.hash(state);
-        self.divine_might.hash(state);
-        self.confiteor.hash(state);
-        self.action_status.hash(state);
-    }
-}
-
-impl Default for Player {
-    fn default() -> Self {
-        Player {
-            time: 0,
-            mp: 10000,
-            damage: 0,
-            global_cooldown: 0,
-            basic_combo: BasicCombo::None,
-            blade_combo: BladeCombo::None,
-            divine_might: DivineMight::None,
-            confiteor: Confiteor::None,
-            action_status: EnumMap::default(),
+        if let Some(spell_speed) = self.spell_speed {
+            stats.spell_speed = spell_speed;
         }
+        stats
     }
 }
 
-impl PartialOrd for Player {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Player {
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        if self.damage * other.time < other.damage * self.time {
-            return cmp::Ordering::Less;
-        }
-        if self.damage * other.time > other.damage * self.time {
-            return cmp::Ordering::Greater;
-        }
-        if self.time != other.time {
-            return self.time.cmp(&other.time);
-        }
-        cmp::Ordering::Equal
-        // calculate_hash(self).cmp(&calculate_hash(other))
+/// Steps of a rotation file, which cannot pin times since the search picks when to press
+fn load_prefix(path: &Path) -> Vec<Step> {
+    let lines = or_exit(sequence::load_sequence(path));
+    if let Some(line) = lines.iter().find(|line| line.at.is_some()) {
+        eprintln!(
+            "{}: line {}: pinned times are not supported in a prefix",
+            path.display(),
+            line.line
+        );
+        process::exit(1);
     }
+    lines.iter().map(|line| line.step).collect()
 }
 
-#[inline(always)]
-fn sub_to_zero(a: u32, b: u32) -> u32 {
-    if a <= b {
-        0
-    } else {
-        a - b
-    }
+fn or_exit<T, E: std::fmt::Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
 }
 
-impl ActionStatus {
-    pub fn tick(&mut self, time: u32, action: &Action) {
-        self.duration = sub_to_zero(self.duration, time);
-        if self.duration == 0 {
-            self.count = 0;
-        };
-
-        let mut time = time;
-        while self.charges < action.max_charges && time > 0 {
-            if self.cooldown > time {
-                self.cooldown -= time;
-                time = 0;
-            } else {
-                time -= self.cooldown;
-                self.charges += 1;
-                self.cooldown = if self.charges == action.max_charges {
-                    0
-                } else {
-                    action.recast
+fn main() {
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Command::Search {
+            horizon,
+            objective,
+            strategy,
+            prefix,
+            forbid,
+            require,
+            max_uses,
+            top,
+            checkpoint,
+            checkpoint_every,
+            resume,
+            format,
+        } => {
+            let actions_map = or_exit(table::load_actions(&cli.actions));
+            let simulator = Simulator::new(actions_map, cli.load_stats());
+            let constraints = Constraints {
+                prefix: prefix.as_deref().map(load_prefix).unwrap_or_default(),
+                forbidden: forbid.clone(),
+                required: require.clone(),
+                max_uses: max_uses.clone(),
+            };
+            let options = SearchOptions {
+                horizon: *horizon,
+                objective: *objective,
+                strategy: *strategy,
+            };
+            if let Some(top) = top {
+                if *strategy != Strategy::BestFirst {
+                    eprintln!("--top only works with the best-first strategy");
+                    process::exit(1);
                 }
+                let result = simulator.alternatives(&options, &constraints, *top);
+                let output = match format {
+                    Format::Text => report::alternatives_text(&result),
+                    Format::Json => report::alternatives_json(&result),
+                    Format::Csv => report::alternatives_csv(&result),
+                };
+                print!("{}", output);
+                return;
             }
+            let result = match checkpoint {
+                Some(path) => {
+                    if !matches!(strategy, Strategy::BestFirst | Strategy::Bound) {
+                        eprintln!(
+                            "--checkpoint only works with the best-first and bound strategies"
+                        );
+                        process::exit(1);
+                    }
+                    let resume = resume
+                        .then(|| or_exit(simulator.load_checkpoint(path, &options, &constraints)));
+                    let checkpoint = CheckpointOptions {
+                        path: path.clone(),
+                        interval: Duration::from_secs(*checkpoint_every),
+                        resume,
+                    };
+                    or_exit(simulator.search_checkpointed(&options, &constraints, checkpoint))
+                }
+                None => simulator.search_constrained(&options, &constraints),
+            };
+            let result = or_exit(result.ok_or("no rotation satisfies the constraints"));
+            let output = match format {
+                Format::Text => report::text(&result),
+                Format::Json => report::json(&result),
+                Format::Csv => report::csv(&result),
+            };
+            print!("{}", output);
         }
-    }
-}
-
-#[derive(Debug)]
-pub enum ActionApplyError {
-    NotReady,
-    MpNotEnough,
-    WaitTooLong,
-    NoneAction,
-}
-
-impl Player {
-    pub fn assign_actions(&mut self, actions_map: &EnumMap<ActionName, Action>) {
-        for (action_name, action) in actions_map {
-            self.action_status[action_name].cooldown = 0;
-            self.action_status[action_name].duration = 0;
-            self.action_status[action_name].charges = action.max_charges;
-            self.action_status[action_name].count = 0;
-        }
-    }
-
-    pub fn recover_mp(&mut self, mp: u32) {
-        self.mp += mp;
-        if self.mp > 10000 {
-            self.mp = 10000
-        };
-    }
-
-    pub fn tick(&mut self, time: u32, actions_map: &EnumMap<ActionName, Action>) {
-        let new_time = self.time + time;
-        self.recover_mp(((new_time / 3000) - (self.time / 3000)) * 200);
-        self.time = new_time;
-        self.global_cooldown = sub_to_zero(self.global_cooldown, time);
-        for (action_name, action) in actions_map {
-            self.action_status[action_name].tick(time, action);
-        }
-    }
-
-    pub fn hit(&mut self, damage: u32) {
-        self.damage += if self.action_status[ActionName::FightOrFlight].duration > 0 {
-            damage / 4 * 5
-        } else {
-            damage
-        };
-    }
-
-    pub fn apply_action(
-        &self,
-        action_name: &ActionName,
-        actions_map: &EnumMap<ActionName, Action>,
-    ) -> Result<Self, ActionApplyError> {
-        let action = &actions_map[*action_name];
-        let mut ret = self.clone();
-
-        let mut wait_time = match action.cooldown_type {
-            CooldownType::Global | CooldownType::GlobalStandalone => ret.global_cooldown,
-            _ => 0,
-        };
-
-        {
-            let action_status = &ret.action_status[*action_name];
-            if action_status.charges == 0 {
-                wait_time = cmp::max(
-                    wait_time,
-                    match action.cooldown_type {
-                        CooldownType::GlobalStandalone | CooldownType::OffGlobal => {
-                            action_status.cooldown
-                        }
-                        _ => 0,
-                    },
-                );
-            }
+        Command::Cycle {
+            period,
+            width,
+            score,
+            format,
+        } => {
+            let actions_map = or_exit(table::load_actions(&cli.actions));
+            let simulator = Simulator::new(actions_map, cli.load_stats());
+            let result = simulator.cycle(&CycleOptions {
+                period: *period,
+                width: *width,
+                score: *score,
+            });
+            let result = or_exit(result.ok_or("no repeating rotation found"));
+            let output = match format {
+                Format::Text => report::cycle_text(&result),
+                Format::Json => report::cycle_json(&result),
+                Format::Csv => report::cycle_csv(&result),
+            };
+            print!("{}", output);
         }
-
-        if wait_time > GLOBAL_COOLDOWN {
-            return Err(ActionApplyError::WaitTooLong);
+        Command::Play { sequence } => {
+            let actions_map = or_exit(table::load_actions(&cli.actions));
+            let action_sequence = or_exit(sequence::load_sequence(sequence));
+            let (steps, player) = or_exit(sequence::play_sequence(
+                &actions_map,
+                cli.load_stats(),
+                &action_sequence,
+            ));
+            print!("{}", report::play_text(&steps, &player));
         }
-
-        ret.tick(wait_time, actions_map);
-
-        // while ret.mp < action.mp_cost {
-        //     ret.tick(((ret.time / 3000) + 1) * 3000 - ret.time, actions_map);
-        // }
-        if ret.mp < action.mp_cost {
-            return Err(ActionApplyError::MpNotEnough);
+        Command::Validate { table } => {
+            let path = table.as_ref().unwrap_or(&cli.actions);
+            let actions_map = or_exit(table::load_actions(path));
+            println!("{}: {} actions ok", path.display(), actions_map.len() - 1);
         }
-        ret.mp -= action.mp_cost;
-        ret.recover_mp(action.mp_restore);
-
-        {
-            let action_status = &mut ret.action_status[*action_name];
-            if action_status.charges == action.max_charges {
-                action_status.cooldown = action.recast;
-            }
-            action_status.charges -= 1;
-            action_status.duration = action.max_duration;
+        Command::Import { snapshot } => {
+            let actions_map = or_exit(table::load_actions(&cli.actions));
+            let snapshot = or_exit(xivapi::load_snapshot(snapshot));
+            let report = xivapi::import(&snapshot, &actions_map);
+            eprint!("{}", report);
+            println!("{}", table::actions_to_json(&report.actions_map));
         }
-
-        match action.cooldown_type {
-            CooldownType::Global | CooldownType::GlobalStandalone => {
-                ret.global_cooldown = GLOBAL_COOLDOWN;
-            }
-            _ => {}
-        };
-
-        let mut potency = action.potency;
-        let mut cast = action.cast;
-
-        match action.name {
-            ActionName::None => {
-                return Err(ActionApplyError::NoneAction);
-            }
-            ActionName::FastBlade => {
-                ret.basic_combo = BasicCombo::FastBlade;
-                ret.blade_combo = BladeCombo::None;
-            }
-            ActionName::FightOrFlight => {}
-            ActionName::RiotBlade => {
-                if let BasicCombo::FastBlade = ret.basic_combo {
-                    potency = action.secondary_potency;
-                    ret.basic_combo = BasicCombo::RiotBlade;
-                } else {
-                    ret.basic_combo = BasicCombo::None;
-                }
-                ret.blade_combo = BladeCombo::None;
-            }
-            ActionName::CircleOfScorn => {}
-            ActionName::GoringBlade => {}
-            ActionName::RoyalAuthority => {
-                if let BasicCombo::RiotBlade = ret.basic_combo {
-                    potency = action.secondary_potency;
-                    let atonement_status = &mut ret.action_status[ActionName::Atonement];
-                    let atonement = &actions_map[ActionName::Atonement];
-                    atonement_status.count = atonement.max_count;
-                    atonement_status.duration = atonement.max_duration;
-                    ret.divine_might = DivineMight::Ready;
-                }
-                ret.basic_combo = BasicCombo::None;
-                ret.blade_combo = BladeCombo::None;
-            }
-            ActionName::HolySpirit => {
-                let requiescat_status = &mut ret.action_status[ActionName::Requiescat];
-                if let DivineMight::Ready = ret.divine_might {
-                    potency = action.secondary_potency;
-                    cast = ANIMATION_LOCK;
-                    ret.divine_might = DivineMight::None;
-                } else if requiescat_status.count > 0 {
-                    requiescat_status.count -= 1;
-                    potency = action.tertiary_potency;
-                    cast = ANIMATION_LOCK;
-                }
-            }
-            ActionName::Requiescat => {
-                let action_status = &mut ret.action_status[*action_name];
-                action_status.count = action.max_count;
-                action_status.duration = action.max_duration;
-                ret.confiteor = Confiteor::Ready;
-            }
-            ActionName::Intervene => {}
-            ActionName::Atonement => {
-                let action_status = &mut ret.action_status[*action_name];
-                if action_status.count == 0 {
-                    return Err(ActionApplyError::NotReady);
-                }
-                action_status.count -= 1;
-            }
-            ActionName::Confiteor => {
-                let requiescat_status = &mut ret.action_status[ActionName::Requiescat];
-                if let Confiteor::None = ret.confiteor {
-                    return Err(ActionApplyError::NotReady);
-                }
-                ret.confiteor = Confiteor::None;
-                ret.blade_combo = BladeCombo::Confiteor;
-                ret.basic_combo = BasicCombo::None;
-                if requiescat_status.count > 0 {
-                    requiescat_status.count -= 1;
-                    potency = action.secondary_potency;
-                }
-            }
-            ActionName::Expiacion => {}
-            ActionName::BladeOfFaith => {
-                let requiescat_status = &mut ret.action_status[ActionName::Requiescat];
-                if let BladeCombo::Confiteor = ret.blade_combo {
-                    if requiescat_status.count > 0 {
-                        requiescat_status.count -= 1;
-                        potency = action.secondary_potency;
-                    }
-                    ret.blade_combo = BladeCombo::BladeOfFaith;
-                    ret.basic_combo = BasicCombo::None;
-                } else {
-                    return Err(ActionApplyError::NotReady);
-                }
-            }
-            ActionName::BladeOfTruth => {
-                let requiescat_status = &mut ret.action_status[ActionName::Requiescat];
-                if let BladeCombo::BladeOfFaith = ret.blade_combo {
-                    if requiescat_status.count > 0 {
-                        requiescat_status.count -= 1;
-                        potency = action.secondary_potency;
-                    }
-                    ret.blade_combo = BladeCombo::BladeOfTruth;
-                    ret.basic_combo = BasicCombo::None;
-                } else {
-                }
-            }
-            ActionName::BladeOfValor => {
-                let requiescat_status = &mut ret.action_status[ActionName::Requiescat];
-                if let BladeCombo::BladeOfTruth = ret.blade_combo {
-                    if requiescat_status.count > 0 {
-                        requiescat_status.count -= 1;
-                        potency = action.secondary_potency;
-                    }
-                    ret.blade_combo = BladeCombo::None;
-                    ret.basic_combo = BasicCombo::None;
-                } else {
-                    return Err(ActionApplyError::NotReady);
-                }
-            }
-        };
-
-        ret.hit(potency);
-        ret.tick(cast, actions_map);
-        Ok(ret)
     }
 }
-
-fn main() {
-    let actions_map = enum_map! {
-        ActionName::None => Action {
-            name: ActionName::FastBlade,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 0,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::FastBlade => Action {
-            name: ActionName::FastBlade,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 2000,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::FightOrFlight => Action {
-            name: ActionName::FightOrFlight,
-            cooldown_type: CooldownType::OffGlobal,
-            cast: ANIMATION_LOCK,
-            recast: 60000,
-            max_duration: 20000,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 0,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::RiotBlade => Action {
-            name: ActionName::RiotBlade,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 1000,
-            potency: 1200,
-            secondary_potency: 2800,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::CircleOfScorn => Action {
-            name: ActionName::CircleOfScorn,
-            cooldown_type: CooldownType::OffGlobal,
-            cast: ANIMATION_LOCK,
-            recast: 30000,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 1000 + 300 * 5,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::GoringBlade => Action {
-            name: ActionName::GoringBlade,
-            cooldown_type: CooldownType::GlobalStandalone,
-            cast: ANIMATION_LOCK,
-            recast: 60000,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 7000,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::RoyalAuthority => Action {
-            name: ActionName::RoyalAuthority,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 1200,
-            secondary_potency: 3800,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::HolySpirit => Action {
-            name: ActionName::HolySpirit,
-            cooldown_type: CooldownType::Global,
-            cast: 1500,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 1000,
-            mp_restore: 0,
-            potency: 3000,
-            secondary_potency: 4500,
-            tertiary_potency: 6500,
-            max_charges: 1,
-        },
-        ActionName::Requiescat => Action {
-            name: ActionName::Requiescat,
-            cooldown_type: CooldownType::OffGlobal,
-            cast: ANIMATION_LOCK,
-            recast: 60000,
-            max_duration: 30000,
-            max_count: 4,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 3000,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::Intervene => Action {
-            name: ActionName::Intervene,
-            cooldown_type: CooldownType::OffGlobal,
-            cast: ANIMATION_LOCK,
-            recast: 30000,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 0,
-            potency: 1500,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 2,
-        },
-        ActionName::Atonement => Action {
-            name: ActionName::Atonement,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 30000,
-            max_count: 3,
-            mp_cost: 0,
-            mp_restore: 400,
-            potency: 3800,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::Confiteor => Action {
-            name: ActionName::Confiteor,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 1000,
-            mp_restore: 0,
-            potency: 4000,
-            secondary_potency: 9000,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::Expiacion => Action {
-            name: ActionName::Expiacion,
-            cooldown_type: CooldownType::OffGlobal,
-            cast: ANIMATION_LOCK,
-            recast: 30000,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 0,
-            mp_restore: 500,
-            potency: 4500,
-            secondary_potency: 0,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::BladeOfFaith => Action {
-            name: ActionName::BladeOfFaith,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 1000,
-            mp_restore: 0,
-            potency: 2000,
-            secondary_potency: 7000,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::BladeOfTruth => Action {
-            name: ActionName::BladeOfTruth,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 1000,
-            mp_restore: 0,
-            potency: 3000,
-            secondary_potency: 8000,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-        ActionName::BladeOfValor => Action {
-            name: ActionName::BladeOfValor,
-            cooldown_type: CooldownType::Global,
-            cast: ANIMATION_LOCK,
-            recast: GLOBAL_COOLDOWN,
-            max_duration: 0,
-            max_count: 0,
-            mp_cost: 1000,
-            mp_restore: 0,
-            potency: 4000,
-            secondary_potency: 9000,
-            tertiary_potency: 0,
-            max_charges: 1,
-        },
-    };
-
-    search(&actions_map);
-}
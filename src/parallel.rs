@@ -0,0 +1,312 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+    thread,
+    time::Instant,
+};
+
+use enum_map::EnumMap;
+
+use crate::{
+    constraints::Constraints,
+    search::{
+        is_better, moves, press_time, record_steps, step_value, Objective, SearchOptions,
+        SearchResult,
+    },
+    stats::Stats,
+    Action, ActionName, Player, StateKey, Step,
+};
+
+/// Number of visited table shards. It does not depend on the thread count, so neither does the
+/// result.
+const SHARDS: usize = 64;
+
+/// States expanded together as one task
+const CHUNK: usize = 64;
+
+/// Pending states this close to the earliest one are expanded in the same round
+const ROUND_WINDOW: u32 = 100;
+
+/// Where a visited state lives: its shard and its index in that shard
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+struct StateRef {
+    shard: usize,
+    index: usize,
+}
+
+/// One part of the visited table, holding the states whose key hashes to it
+#[derive(Debug, Default)]
+struct Shard {
+    index: HashMap<StateKey, usize>,
+    values: Vec<u32>,
+    history: Vec<(StateRef, Step)>,
+}
+
+/// A state reached by expanding a pending one, on its way to its shard. Like pending states it
+/// only keeps its key and damage, from which [`Player::from_key`] rebuilds the player.
+#[derive(Debug)]
+struct Child {
+    key: StateKey,
+    parent: StateRef,
+    step: Step,
+    value: u32,
+    time: u32,
+    damage: u32,
+}
+
+/// What expanding one chunk produced
+#[derive(Debug)]
+struct ChunkOutput {
+    children: Vec<Vec<Child>>,
+    best: Option<(u32, u32, StateRef)>,
+    expanded: u64,
+}
+
+/// `DefaultHasher::new` uses fixed keys, so a state lands in the same shard on every run
+fn shard_of(key: &StateKey) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % SHARDS as u64) as usize
+}
+
+/// Runs `task` on every index from 0 to `tasks` with `threads` workers, returning the results in
+/// index order. Each worker starts on its own contiguous run of indices and, once that is done,
+/// steals from the back of the other workers' runs.
+fn run_stealing<R: Send>(threads: usize, tasks: usize, task: impl Fn(usize) -> R + Sync) -> Vec<R> {
+    let run = tasks.div_ceil(threads).max(1);
+    let queues: Vec<Mutex<VecDeque<usize>>> = (0..threads)
+        .map(|worker| Mutex::new((worker * run..((worker + 1) * run).min(tasks)).collect()))
+        .collect();
+    let mut results: Vec<Option<R>> = (0..tasks).map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|worker| {
+                let (queues, task) = (&queues, &task);
+                scope.spawn(move || {
+                    let mut done = vec![];
+                    loop {
+                        // Never hold two queue locks at once
+                        let own = queues[worker].lock().unwrap().pop_front();
+                        let next = own.or_else(|| {
+                            (1..threads).find_map(|offset| {
+                                queues[(worker + offset) % threads]
+                                    .lock()
+                                    .unwrap()
+                                    .pop_back()
+                            })
+                        });
+                        match next {
+                            Some(index) => done.push((index, task(index))),
+                            None => break,
+                        }
+                    }
+                    done
+                })
+            })
+            .collect();
+        for handle in handles {
+            for (index, result) in handle.join().unwrap() {
+                results[index] = Some(result);
+            }
+        }
+    });
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// Exhaustive search like the best-first one, spread over `threads` threads.
+///
+/// Pending states are kept in time order and expanded in rounds: each round takes the states
+/// within `ROUND_WINDOW` of the earliest, splits them into chunks and lets the workers expand
+/// the chunks, stealing from each other when their own run out. The children are then merged
+/// into the sharded visited table, one shard per task, keeping the best value per state as the
+/// best-first search does and putting improved states back into the pending set. A state whose
+/// parent was in the same round may be expanded before its value is final, and is expanded
+/// again once improved. For `TimeTo` the search stops once every pending state is later than
+/// the earliest one found over the target.
+///
+/// Chunks and shards are merged in a fixed order and ties keep the first value in that order,
+/// so the result is the same for every run and every thread count. Returns `None` if no state
+/// meets the constraints.
+///
+/// The pending set is far wider than the best-first heap, so it holds state keys instead of
+/// players, and the players are rebuilt from their keys when a round expands them.
+pub fn search(
+    actions_map: &EnumMap<ActionName, Action>,
+    stats: Stats,
+    options: &SearchOptions,
+    constraints: &Constraints,
+    threads: usize,
+) -> Option<SearchResult> {
+    let start = Instant::now();
+    let objective = options.objective;
+    let threads = threads.max(1);
+    let player = Player::new(stats, actions_map);
+
+    let mut shards: Vec<Shard> = (0..SHARDS).map(|_| Shard::default()).collect();
+    let progress = constraints.start();
+    let key = constraints.state_key(&player, &progress);
+    let root = StateRef {
+        shard: shard_of(&key),
+        index: 0,
+    };
+    let shard = &mut shards[root.shard];
+    shard.index.insert(key.clone(), 0);
+    shard.values.push(0);
+    shard.history.push((root, Step::Action(ActionName::None)));
+
+    let mut pending = BTreeMap::new();
+    pending.insert((player.time, root), (key, player.damage));
+
+    let mut cnt = 0;
+    // Value, time and state of the best state that meets the constraints
+    let mut best: Option<(u32, u32, StateRef)> = None;
+    let mut peak_heap = pending.len();
+
+    while let Some((&(earliest, _), _)) = pending.first_key_value() {
+        if let Objective::TimeTo(target) = objective {
+            // Everything left is later than the earliest state over the target
+            if best.is_some_and(|(value, time, _)| value >= target && earliest >= time) {
+                break;
+            }
+        }
+        let rest = pending.split_off(&(earliest + ROUND_WINDOW, StateRef::default()));
+        let batch: Vec<(StateRef, u32, StateKey, u32)> = std::mem::replace(&mut pending, rest)
+            .into_iter()
+            .map(|((_, state), (key, damage))| {
+                let value = shards[state.shard].values[state.index];
+                (state, value, key, damage)
+            })
+            .collect();
+
+        let chunks = batch.len().div_ceil(CHUNK);
+        let outputs = run_stealing(threads, chunks, |chunk| {
+            let mut output = ChunkOutput {
+                children: (0..SHARDS).map(|_| vec![]).collect(),
+                best: None,
+                expanded: 0,
+            };
+            for (state, value, key, damage) in batch.iter().skip(chunk * CHUNK).take(CHUNK) {
+                let mut values = key.values();
+                let player = &Player::from_key(&mut values, *damage, stats, actions_map);
+                let progress = &constraints.progress_from(&mut values);
+                output.expanded += 1;
+                let improves = match output.best {
+                    _ if !constraints.is_complete(progress) => false,
+                    Some((best_value, best_time, _)) => {
+                        is_better(objective, (*value, player.time), (best_value, best_time))
+                    }
+                    None => true,
+                };
+                if improves {
+                    output.best = Some((*value, player.time, *state));
+                }
+                if let Objective::Burst { end, .. } = objective {
+                    if player.time >= end {
+                        continue;
+                    }
+                }
+                for step in constraints.moves(progress, moves(player)) {
+                    let new_player = match player.apply_step(&step, actions_map) {
+                        Ok(new_player) if new_player.time <= options.horizon => new_player,
+                        _ => continue,
+                    };
+                    let time = press_time(actions_map, player, &step);
+                    let progress = match constraints.advance(
+                        actions_map,
+                        progress,
+                        &step,
+                        time,
+                        &new_player,
+                    ) {
+                        Some(progress) => progress,
+                        None => continue,
+                    };
+                    let new_value =
+                        step_value(objective, actions_map, player, &step, &new_player, *value);
+                    let key = constraints.state_key(&new_player, &progress);
+                    output.children[shard_of(&key)].push(Child {
+                        key,
+                        parent: *state,
+                        step,
+                        value: new_value,
+                        time: new_player.time,
+                        damage: new_player.damage,
+                    });
+                }
+            }
+            output
+        });
+
+        let mut by_shard: Vec<Vec<Child>> = (0..SHARDS).map(|_| vec![]).collect();
+        for output in outputs {
+            cnt += output.expanded;
+            if let Some((value, time, state)) = output.best {
+                if best.is_none_or(|(best_value, best_time, _)| {
+                    is_better(objective, (value, time), (best_value, best_time))
+                }) {
+                    best = Some((value, time, state));
+                }
+            }
+            for (children, shard_children) in by_shard.iter_mut().zip(output.children) {
+                children.extend(shard_children);
+            }
+        }
+
+        let tasks: Vec<Mutex<(&mut Shard, Vec<Child>)>> =
+            shards.iter_mut().zip(by_shard).map(Mutex::new).collect();
+        let improved = run_stealing(threads, SHARDS, |shard_index| {
+            let mut task = tasks[shard_index].lock().unwrap();
+            let (shard, children) = &mut *task;
+            let mut improved = vec![];
+            for child in children.drain(..) {
+                let index = match shard.index.get(&child.key) {
+                    Some(&index) if child.value > shard.values[index] => index,
+                    Some(_) => continue,
+                    None => {
+                        let index = shard.values.len();
+                        shard.index.insert(child.key.clone(), index);
+                        shard.values.push(0);
+                        shard.history.push((child.parent, child.step));
+                        index
+                    }
+                };
+                shard.values[index] = child.value;
+                shard.history[index] = (child.parent, child.step);
+                let state = StateRef {
+                    shard: shard_index,
+                    index,
+                };
+                improved.push(((child.time, state), (child.key, child.damage)));
+            }
+            improved
+        });
+        drop(tasks);
+        pending.extend(improved.into_iter().flatten());
+        peak_heap = peak_heap.max(pending.len());
+    }
+
+    let (value, _, mut current) = best?;
+    let mut path = vec![];
+    loop {
+        let (parent, step) = shards[current.shard].history[current.index];
+        if let Step::Action(ActionName::None) = step {
+            break;
+        }
+        path.push(step);
+        current = parent;
+    }
+    path.reverse();
+    let (steps, last) = record_steps(actions_map, player, &path);
+    Some(SearchResult {
+        objective,
+        horizon: options.horizon,
+        steps,
+        damage: last.damage,
+        time: last.time,
+        value,
+        nodes: cnt,
+        peak_heap,
+        elapsed: start.elapsed(),
+    })
+}
@@ -0,0 +1,217 @@
+use serde_json::json;
+
+use crate::{
+    alternatives::AlternativesResult,
+    cycle::CycleResult,
+    search::{SearchResult, StepRecord},
+    Player,
+};
+
+fn seconds(time: u32) -> f64 {
+    time as f64 / 1000.0
+}
+
+fn dps(damage: u32, time: u32) -> f64 {
+    if time == 0 {
+        0.0
+    } else {
+        damage as f64 / time as f64 * 1000.0
+    }
+}
+
+/// Human readable table of the best rotation followed by a summary
+pub fn text(result: &SearchResult) -> String {
+    let mut out = steps_text(&result.steps);
+    out += &format!(
+        "damage {} over {:.2}s, {:.1} dps\n",
+        result.damage,
+        seconds(result.time),
+        result.dps()
+    );
+    out += &format!("{:?} score {:.1}\n", result.objective, result.score());
+    out += &format!(
+        "expanded {} nodes, peak heap {}, took {:.2?}\n",
+        result.nodes, result.peak_heap, result.elapsed
+    );
+    out
+}
+
+pub fn json(result: &SearchResult) -> String {
+    let value = json!({
+        "steps": steps_json(&result.steps),
+        "damage": result.damage,
+        "time": result.time,
+        "dps": result.dps(),
+        "objective": format!("{:?}", result.objective),
+        "horizon": result.horizon,
+        "score": result.score(),
+        "nodes": result.nodes,
+        "peak_heap": result.peak_heap,
+        "elapsed_ms": result.elapsed.as_millis() as u64,
+    });
+    serde_json::to_string_pretty(&value).unwrap() + "\n"
+}
+
+/// One row per step, times in milliseconds
+pub fn csv(result: &SearchResult) -> String {
+    steps_csv(&result.steps)
+}
+
+/// A replayed rotation file followed by where it ended up
+pub fn play_text(steps: &[StepRecord], player: &Player) -> String {
+    let mut out = steps_text(steps);
+    out += &format!(
+        "damage {} over {:.2}s, {:.1} dps, {} MP left\n",
+        player.damage(),
+        seconds(player.time()),
+        dps(player.damage(), player.time()),
+        player.mp()
+    );
+    out
+}
+
+/// The loop followed by a summary, with step times counted from the start of the loop
+pub fn cycle_text(result: &CycleResult) -> String {
+    let mut out = steps_text(&result.steps);
+    out += &format!(
+        "loop of {:.2}s from {:.2}s over {:.2}s of search periods, damage {} per pass, {:.1} dps\n",
+        seconds(result.time),
+        seconds(result.start),
+        seconds(result.period),
+        result.damage,
+        result.dps()
+    );
+    out += &format!(
+        "expanded {} nodes, took {:.2?}\n",
+        result.nodes, result.elapsed
+    );
+    out
+}
+
+pub fn cycle_json(result: &CycleResult) -> String {
+    let value = json!({
+        "opener": steps_json(&result.opener),
+        "start": result.start,
+        "steps": steps_json(&result.steps),
+        "damage": result.damage,
+        "time": result.time,
+        "dps": result.dps(),
+        "period": result.period,
+        "nodes": result.nodes,
+        "elapsed_ms": result.elapsed.as_millis() as u64,
+    });
+    serde_json::to_string_pretty(&value).unwrap() + "\n"
+}
+
+/// One row per step of the loop, times in milliseconds from its start
+pub fn cycle_csv(result: &CycleResult) -> String {
+    steps_csv(&result.steps)
+}
+
+/// Each rotation with where it leaves the best one, followed by a summary
+pub fn alternatives_text(result: &AlternativesResult) -> String {
+    let mut out = String::new();
+    for (rank, alternative) in result.alternatives.iter().enumerate() {
+        out += &format!(
+            "#{}: damage {} over {:.2}s, {:.1} dps, value {}\n",
+            rank + 1,
+            alternative.damage,
+            seconds(alternative.time),
+            alternative.dps(),
+            alternative.value
+        );
+        if let Some(time) = alternative.diverges_at {
+            out += &format!(
+                "shares {} actions with #1, diverges at {:.2}s\n",
+                alternative.shared,
+                seconds(time)
+            );
+        }
+        out += &steps_text(&alternative.steps);
+        out += "\n";
+    }
+    out += &format!(
+        "found {} rotations, expanded {} nodes, took {:.2?}\n",
+        result.alternatives.len(),
+        result.nodes,
+        result.elapsed
+    );
+    out
+}
+
+pub fn alternatives_json(result: &AlternativesResult) -> String {
+    let alternatives: Vec<_> = result
+        .alternatives
+        .iter()
+        .map(|alternative| {
+            json!({
+                "steps": steps_json(&alternative.steps),
+                "damage": alternative.damage,
+                "time": alternative.time,
+                "dps": alternative.dps(),
+                "value": alternative.value,
+                "shared": alternative.shared,
+                "diverges_at": alternative.diverges_at,
+            })
+        })
+        .collect();
+    let value = json!({
+        "alternatives": alternatives,
+        "objective": format!("{:?}", result.objective),
+        "horizon": result.horizon,
+        "nodes": result.nodes,
+        "elapsed_ms": result.elapsed.as_millis() as u64,
+    });
+    serde_json::to_string_pretty(&value).unwrap() + "\n"
+}
+
+/// One row per step of every rotation, numbered from 1 for the best, times in milliseconds
+pub fn alternatives_csv(result: &AlternativesResult) -> String {
+    let mut out = String::from("rank,time,step,damage\n");
+    for (rank, alternative) in result.alternatives.iter().enumerate() {
+        for record in &alternative.steps {
+            out += &format!(
+                "{},{},{:?},{}\n",
+                rank + 1,
+                record.time,
+                record.step,
+                record.damage
+            );
+        }
+    }
+    out
+}
+
+fn steps_text(steps: &[StepRecord]) -> String {
+    let mut out = String::new();
+    for record in steps {
+        out += &format!(
+            "{:>7.2}s  {:<20} {:>8}\n",
+            seconds(record.time),
+            format!("{:?}", record.step),
+            record.damage
+        );
+    }
+    out
+}
+
+fn steps_json(steps: &[StepRecord]) -> Vec<serde_json::Value> {
+    steps
+        .iter()
+        .map(|record| {
+            json!({
+                "time": record.time,
+                "step": format!("{:?}", record.step),
+                "damage": record.damage,
+            })
+        })
+        .collect()
+}
+
+fn steps_csv(steps: &[StepRecord]) -> String {
+    let mut out = String::from("time,step,damage\n");
+    for record in steps {
+        out += &format!("{},{:?},{}\n", record.time, record.step, record.damage);
+    }
+    out
+}
@@ -1,11 +1,26 @@
 // use min_max_heap::MinMaxHeap;
-use std::collections::{BinaryHeap, HashMap};
+use std::{
+    cmp,
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
 
 use enum_map::EnumMap;
 
-use crate::{calculate_hash, Action, ActionName, Player};
+use crate::{
+    beam::{self, BeamScore},
+    bound::DamageBound,
+    checkpoint::{self, CheckpointError, CheckpointOptions, SnapshotRef},
+    constraints::{Constraints, Progress},
+    parallel,
+    sequence::parse_time,
+    stats::Stats,
+    Action, ActionName, Player, Step,
+};
 
-const MAX_TIME: u32 = 10000;
+pub const MAX_TIME: u32 = 10000;
 
 const ACTION_NAME_LIST: [ActionName; 15] = [
     ActionName::FastBlade,
@@ -25,69 +40,577 @@ const ACTION_NAME_LIST: [ActionName; 15] = [
     ActionName::BladeOfValor,
 ];
 
-pub fn search(actions_map: &EnumMap<ActionName, Action>) {
-    let mut player = Player::default();
-    player.assign_actions(actions_map);
+/// What the search maximizes, evaluated on states that end by the horizon
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Objective {
+    /// Total damage dealt by the horizon, expanding the highest damage states first
+    Damage,
+    /// Average DPS over [0, horizon], expanding the highest damage per time states first
+    Dps,
+    /// Damage landing inside [start, end): hits when they are pressed and ticks when they land
+    Burst { start: u32, end: u32 },
+    /// Shortest time to deal the given damage, expanding the earliest states first
+    TimeTo(u32),
+}
 
-    let h = calculate_hash(&player);
+impl FromStr for Objective {
+    type Err = String;
 
-    // let mut heap = MinMaxHeap::new();
-    let mut heap = BinaryHeap::new();
-    let mut damages = HashMap::new();
-    let mut history = HashMap::new();
-    damages.insert(h, player.damage);
-    history.insert(h, (0u64, ActionName::None));
-    heap.push(player);
+    /// Parses `damage`, `dps`, `burst:20s-40s` or `time-to:500000`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match text.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (text, None),
+        };
+        match (name, argument) {
+            ("damage", None) => Ok(Objective::Damage),
+            ("dps", None) => Ok(Objective::Dps),
+            ("burst", Some(window)) => {
+                let (start, end) = window
+                    .split_once('-')
+                    .ok_or_else(|| format!("expected burst:START-END, got `{}`", text))?;
+                let start = parse_time(start.trim()).map_err(|e| e.to_string())?;
+                let end = parse_time(end.trim()).map_err(|e| e.to_string())?;
+                if start >= end {
+                    return Err(format!("burst window `{}` is empty", window));
+                }
+                Ok(Objective::Burst { start, end })
+            }
+            ("time-to", Some(damage)) => damage
+                .trim()
+                .parse()
+                .map(Objective::TimeTo)
+                .map_err(|_| format!("cannot parse damage `{}`", damage)),
+            _ => Err(format!(
+                "unknown objective `{}`, expected damage, dps, burst:START-END or time-to:DAMAGE",
+                text
+            )),
+        }
+    }
+}
 
-    let mut cnt = 0;
-    let mut ans = 0;
-    let mut best_h: u64 = 0;
-
-    while !heap.is_empty() {
-        // let mut player = heap.pop_max().unwrap();
-        let mut player = heap.pop().unwrap();
-        let h = calculate_hash(&player);
-        player.damage = *damages.get(&h).unwrap();
-        if player.damage > ans {
-            ans = player.damage;
-            best_h = h;
-            println!("new best! {}", ans);
+/// How the search explores the state space
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Expands every reachable state in the objective's order
+    #[default]
+    BestFirst,
+    /// A* on an upper bound of the final value, pruning states whose bound cannot beat the best
+    /// value found so far and stopping once no queued state can. The bound is loose enough that
+    /// horizons past about 15 s run out of memory, see [`DamageBound`].
+    Bound,
+    /// Keeps only the `width` best states by `score` at each GCD slot, see [`beam::search`]
+    Beam { width: usize, score: BeamScore },
+    /// Visits every reachable state like `BestFirst`, spread over `threads` threads, see
+    /// [`parallel::search`]
+    Parallel { threads: usize },
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    /// Parses `best-first`, `bound`, `beam:WIDTH`, `beam:WIDTH:SCORE`, `parallel` for one
+    /// thread per core or `parallel:THREADS`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split(':');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some("best-first"), None, _, _) => Ok(Strategy::BestFirst),
+            (Some("bound"), None, _, _) => Ok(Strategy::Bound),
+            (Some("beam"), Some(width), score, None) => {
+                let width = match width.trim().parse() {
+                    Ok(width) if width > 0 => width,
+                    _ => return Err(format!("cannot parse beam width `{}`", width)),
+                };
+                let score = score.map_or(Ok(BeamScore::default()), str::parse)?;
+                Ok(Strategy::Beam { width, score })
+            }
+            (Some("parallel"), threads, None, _) => {
+                let threads = match threads {
+                    Some(threads) => match threads.trim().parse() {
+                        Ok(threads) if threads > 0 => threads,
+                        _ => return Err(format!("cannot parse thread count `{}`", threads)),
+                    },
+                    None => thread::available_parallelism().map_or(1, |threads| threads.get()),
+                };
+                Ok(Strategy::Parallel { threads })
+            }
+            _ => Err(format!(
+                "unknown strategy `{}`, expected best-first, bound, beam:WIDTH[:SCORE] or parallel[:THREADS]",
+                text
+            )),
         }
-        cnt += 1;
-        if cnt % 50000 == 0 {
-            println!("{} {}", cnt, heap.len());
+    }
+}
+
+/// Settings of a single search run
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// No step may end after this time, in milliseconds
+    pub horizon: u32,
+    pub objective: Objective,
+    pub strategy: Strategy,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            horizon: MAX_TIME,
+            objective: Objective::Damage,
+            strategy: Strategy::BestFirst,
         }
-        for action_name in &ACTION_NAME_LIST {
-            let new_player = player.apply_action(action_name, actions_map);
-            if let Ok(new_player) = new_player {
-                if new_player.time <= MAX_TIME {
-                    let new_h = calculate_hash(&new_player);
-                    if !damages.contains_key(&new_h) {
-                        damages.insert(new_h, new_player.damage);
-                        history.insert(new_h, (h, *action_name));
-                        heap.push(new_player);
-                    } else if *damages.get(&new_h).unwrap() > new_player.damage {
-                        damages.insert(new_h, new_player.damage);
-                        history.insert(new_h, (h, *action_name));
-                    }
-                }
+    }
+}
+
+/// One step of the best rotation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StepRecord {
+    pub step: Step,
+    /// When the step is pressed, after waiting for its cooldowns
+    pub time: u32,
+    /// Damage dealt from the end of the previous step to the end of this one, including ticks
+    pub damage: u32,
+}
+
+/// Best rotation found by [`search`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub objective: Objective,
+    pub horizon: u32,
+    pub steps: Vec<StepRecord>,
+    pub damage: u32,
+    pub time: u32,
+    /// Damage counted by the objective, which differs from `damage` for burst windows
+    pub value: u32,
+    /// Number of states expanded, counting a state again when an improved path re-queues it
+    pub nodes: u64,
+    pub peak_heap: usize,
+    pub elapsed: Duration,
+}
+
+impl SearchResult {
+    pub fn dps(&self) -> f64 {
+        if self.time == 0 {
+            0.0
+        } else {
+            self.damage as f64 / self.time as f64 * 1000.0
+        }
+    }
+
+    /// The objective's value for the best rotation: damage, DPS over the horizon, burst window
+    /// damage, or seconds to reach the target, infinite if it was never reached
+    pub fn score(&self) -> f64 {
+        match self.objective {
+            Objective::Damage => self.damage as f64,
+            Objective::Dps => self.damage as f64 / self.horizon as f64 * 1000.0,
+            Objective::Burst { .. } => self.value as f64,
+            Objective::TimeTo(target) if self.damage >= target => self.time as f64 / 1000.0,
+            Objective::TimeTo(_) => f64::INFINITY,
+        }
+    }
+}
+
+/// A frontier state together with the damage the objective has counted along its path
+#[derive(Debug, Clone)]
+struct Node {
+    objective: Objective,
+    value: u32,
+    /// Upper bound on the value of any rotation through this node, expanded in bound order when
+    /// set
+    bound: Option<u64>,
+    /// Index of the state in the visited tables
+    id: usize,
+    player: Player,
+    progress: Progress,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    /// Greater nodes are expanded first
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        if let (Some(bound), Some(other_bound)) = (self.bound, other.bound) {
+            if bound != other_bound {
+                return bound.cmp(&other_bound);
             }
         }
+        match self.objective {
+            Objective::Damage | Objective::Burst { .. } => self
+                .value
+                .cmp(&other.value)
+                .then(other.player.time.cmp(&self.player.time)),
+            Objective::Dps => self.player.cmp(&other.player),
+            Objective::TimeTo(_) => other
+                .player
+                .time
+                .cmp(&self.player.time)
+                .then(self.value.cmp(&other.value)),
+        }
     }
+}
+
+pub(crate) fn press_time(
+    actions_map: &EnumMap<ActionName, Action>,
+    player: &Player,
+    step: &Step,
+) -> u32 {
+    match step {
+        Step::Action(action_name) => player.time + player.ready_in(&actions_map[*action_name]),
+        Step::Wait(_) => player.time,
+    }
+}
+
+/// Every step the search tries from the player: each action, or waiting for the next cooldown
+pub(crate) fn moves(player: &Player) -> impl Iterator<Item = Step> {
+    ACTION_NAME_LIST
+        .iter()
+        .map(|action_name| Step::Action(*action_name))
+        .chain(player.next_ready_time().map(Step::Wait))
+}
 
-    println!("Done, highest={}, count={}", ans, cnt);
+/// Value of the state after the step, adding what the step dealt that the objective counts
+pub(crate) fn step_value(
+    objective: Objective,
+    actions_map: &EnumMap<ActionName, Action>,
+    player: &Player,
+    step: &Step,
+    new_player: &Player,
+    value: u32,
+) -> u32 {
+    match objective {
+        Objective::Burst { start, end } => {
+            value + player.damage_within(step, actions_map, start, end)
+        }
+        _ => value + new_player.damage - player.damage,
+    }
+}
+
+/// Whether a state with `value` at `time` beats the best so far
+pub(crate) fn is_better(
+    objective: Objective,
+    (value, time): (u32, u32),
+    (best, best_time): (u32, u32),
+) -> bool {
+    match objective {
+        Objective::TimeTo(target) => match (value >= target, best >= target) {
+            (true, true) => time < best_time,
+            (reached, best_reached) if reached != best_reached => reached,
+            _ => value > best,
+        },
+        _ => value > best,
+    }
+}
 
-    let mut current_h = best_h;
-    let mut current_action: ActionName;
-    let mut action_history = vec![];
+/// Follows the parent links of `history` back from the state to the root, which links to itself
+/// with `ActionName::None`
+pub(crate) fn trace(history: &[(usize, Step)], id: usize) -> Vec<Step> {
+    let mut current_id = id;
+    let mut current_step: Step;
+    let mut path = vec![];
     loop {
-        (current_h, current_action) = *history.get(&current_h).unwrap();
-        if let ActionName::None = current_action {
+        (current_id, current_step) = history[current_id];
+        if let Step::Action(ActionName::None) = current_step {
             break;
         } else {
-            action_history.push(current_action);
+            path.push(current_step);
         }
     }
-    action_history.reverse();
-    println!("{:#?}", action_history);
+    path.reverse();
+    path
+}
+
+/// Replays the steps to recover when each one was pressed and how much damage it dealt
+pub(crate) fn record_steps(
+    actions_map: &EnumMap<ActionName, Action>,
+    mut player: Player,
+    steps: &[Step],
+) -> (Vec<StepRecord>, Player) {
+    let mut records = vec![];
+    for step in steps {
+        let time = press_time(actions_map, &player, step);
+        let next = player.apply_step(step, actions_map).unwrap();
+        records.push(StepRecord {
+            step: *step,
+            time,
+            damage: next.damage - player.damage,
+        });
+        player = next;
+    }
+    (records, player)
+}
+
+/// Finds the rotation with the best objective value among those ending by the horizon.
+///
+/// Every step moves time forward and the state key includes the time, so states form a DAG.
+/// What a state can still gain depends only on its key, so the best value of a state is the best
+/// value of any parent plus what the step to it gains. The search keeps that best value per
+/// state in `damages`, with the step that achieved it in `history`. Whenever a path improves on
+/// a visited state, the state is re-queued so the improvement reaches its descendants, and queue
+/// entries holding an outdated value are dropped when popped. The search only stops when the
+/// queue is empty, so every state has its best value by then and the best of them is optimal.
+/// The one early exit is `TimeTo`, where nodes are popped in time order: all parents of a state
+/// are earlier, so its value is final when it is popped and the first one over the target wins.
+///
+/// With constraints, the progress through them is part of the state key, steps that break them
+/// are never taken, and only states that meet them all can be the result. Returns `None` if no
+/// state does.
+///
+/// With a checkpoint, the visited tables, the queued states and the best state so far are saved
+/// every interval, together with the best rotation so far, and a run can start from a saved
+/// snapshot instead of the initial state. Only ids are saved: loading the snapshot replays the
+/// history tree from the root to recover the key of every visited state and the player of every
+/// queued one, see [`checkpoint::load`]. Beam and parallel searches do not checkpoint. If a
+/// checkpoint cannot be saved, the search stops and returns the error.
+pub fn search(
+    actions_map: &EnumMap<ActionName, Action>,
+    stats: Stats,
+    options: &SearchOptions,
+    constraints: &Constraints,
+    checkpoint: Option<CheckpointOptions>,
+) -> Result<Option<SearchResult>, CheckpointError> {
+    let start = Instant::now();
+    let objective = options.objective;
+    let player = Player::new(stats, actions_map);
+    let initial = player.clone();
+    let damage_bound = match options.strategy {
+        Strategy::BestFirst => None,
+        Strategy::Bound => Some(DamageBound::new(actions_map, &stats)),
+        Strategy::Beam { width, score } => {
+            return Ok(beam::search(
+                actions_map,
+                stats,
+                options,
+                constraints,
+                width,
+                score,
+            ))
+        }
+        Strategy::Parallel { threads } => {
+            return Ok(parallel::search(
+                actions_map,
+                stats,
+                options,
+                constraints,
+                threads,
+            ))
+        }
+    };
+    // Upper bound on the value of any rotation through the player, ordering the heap unless the
+    // objective is time based
+    let upper_bound = |player: &Player, value: u32| {
+        damage_bound
+            .as_ref()
+            .map(|bound| value as u64 + bound.remaining(player, options.horizon))
+    };
+    let heap_bound = |upper: Option<u64>| match objective {
+        Objective::TimeTo(_) => None,
+        _ => upper,
+    };
+    // Whether nothing under the upper bound can beat the best state found so far
+    let beaten = |best: Option<(u32, usize)>, upper: u64| {
+        best.is_some_and(|(value, _)| upper <= value as u64)
+    };
+
+    let result = |history: &[(usize, Step)], best: Option<(u32, usize)>, cnt, peak_heap| {
+        let (value, best_id) = best?;
+        let path = trace(history, best_id);
+        let (steps, last) = record_steps(actions_map, initial.clone(), &path);
+        Some(SearchResult {
+            objective,
+            horizon: options.horizon,
+            steps,
+            damage: last.damage,
+            time: last.time,
+            value,
+            nodes: cnt,
+            peak_heap,
+            elapsed: start.elapsed(),
+        })
+    };
+    let (saving, resume) = match checkpoint {
+        Some(CheckpointOptions {
+            path,
+            interval,
+            resume,
+        }) => (Some((path, interval)), resume),
+        None => (None, None),
+    };
+    let mut last_save = Instant::now();
+
+    // Every visited state gets an id, which indexes `damages` and `history`
+    let mut visited = HashMap::new();
+    let mut damages = vec![];
+    let mut history = vec![];
+    // let mut heap = MinMaxHeap::new();
+    let mut heap = BinaryHeap::new();
+    let mut cnt = 0;
+    // Value and id of the best state that meets the constraints
+    let mut best: Option<(u32, usize)> = None;
+    let mut peak_heap = 1;
+
+    match resume {
+        None => {
+            let progress = constraints.start();
+            visited.insert(constraints.state_key(&player, &progress), 0);
+            damages.push(0);
+            history.push((0, Step::Action(ActionName::None)));
+            heap.push(Node {
+                objective,
+                value: 0,
+                bound: heap_bound(upper_bound(&player, 0)),
+                id: 0,
+                player,
+                progress,
+            });
+        }
+        Some(snapshot) => {
+            damages = snapshot.damages;
+            history = snapshot.history;
+            best = snapshot.best;
+            cnt = snapshot.nodes;
+            peak_heap = snapshot.peak_heap;
+            visited = snapshot.visited;
+            for (id, player, progress) in snapshot.frontier {
+                heap.push(Node {
+                    objective,
+                    value: damages[id],
+                    bound: heap_bound(upper_bound(&player, damages[id])),
+                    id,
+                    player,
+                    progress,
+                });
+            }
+        }
+    }
+
+    while let Some(node) = heap.pop() {
+        if let Some((path, interval)) = &saving {
+            if last_save.elapsed() >= *interval {
+                let frontier = heap
+                    .iter()
+                    .chain([&node])
+                    .filter(|queued| queued.value == damages[queued.id])
+                    .map(|queued| queued.id)
+                    .collect();
+                let snapshot = SnapshotRef {
+                    settings: checkpoint::settings(actions_map, options, constraints, &stats),
+                    damages: &damages,
+                    history: &history,
+                    frontier,
+                    best,
+                    nodes: cnt,
+                    peak_heap,
+                };
+                let best = result(&history, best, cnt, peak_heap);
+                snapshot
+                    .save(path, best.as_ref())
+                    .map_err(|e| CheckpointError::Save(path.clone(), e))?;
+                last_save = Instant::now();
+            }
+        }
+        let id = node.id;
+        if node.value < damages[id] {
+            // A better path to this state was queued after this entry
+            continue;
+        }
+        if node.bound.is_some_and(|bound| beaten(best, bound)) {
+            // Nodes come out in bound order, so nothing left can beat the best
+            break;
+        }
+        cnt += 1;
+        let complete = constraints.is_complete(&node.progress);
+        if complete && best.is_none_or(|(ans, _)| node.value > ans) {
+            best = Some((node.value, id));
+        }
+        match objective {
+            // Nodes come out in time order, so the first one over the target is the earliest
+            Objective::TimeTo(target) if complete && node.value >= target => break,
+            // Nothing pressed after the window can add to it
+            Objective::Burst { end, .. } if node.player.time >= end => continue,
+            _ => {}
+        }
+
+        let player = &node.player;
+        for step in constraints.moves(&node.progress, moves(player)) {
+            let new_player = player.apply_step(&step, actions_map);
+            if let Ok(new_player) = new_player {
+                if new_player.time <= options.horizon {
+                    let time = press_time(actions_map, player, &step);
+                    let progress = match constraints.advance(
+                        actions_map,
+                        &node.progress,
+                        &step,
+                        time,
+                        &new_player,
+                    ) {
+                        Some(progress) => progress,
+                        None => continue,
+                    };
+                    let new_value = step_value(
+                        objective,
+                        actions_map,
+                        player,
+                        &step,
+                        &new_player,
+                        node.value,
+                    );
+                    let upper = upper_bound(&new_player, new_value);
+                    let pruned = match (objective, upper) {
+                        (_, None) => false,
+                        (Objective::TimeTo(target), Some(upper)) => upper < target as u64,
+                        (_, Some(upper)) => beaten(best, upper),
+                    };
+                    if pruned {
+                        continue;
+                    }
+                    match visited.entry(constraints.state_key(&new_player, &progress)) {
+                        Entry::Vacant(entry) => {
+                            let new_id = damages.len();
+                            entry.insert(new_id);
+                            damages.push(new_value);
+                            history.push((id, step));
+                            heap.push(Node {
+                                objective,
+                                value: new_value,
+                                bound: heap_bound(upper),
+                                id: new_id,
+                                player: new_player,
+                                progress,
+                            });
+                            peak_heap = peak_heap.max(heap.len());
+                        }
+                        Entry::Occupied(entry) => {
+                            let new_id = *entry.get();
+                            if new_value > damages[new_id] {
+                                damages[new_id] = new_value;
+                                history[new_id] = (id, step);
+                                heap.push(Node {
+                                    objective,
+                                    value: new_value,
+                                    bound: heap_bound(upper),
+                                    id: new_id,
+                                    player: new_player,
+                                    progress,
+                                });
+                                peak_heap = peak_heap.max(heap.len());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result(&history, best, cnt, peak_heap))
 }
@@ -1,63 +1,206 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
 use enum_map::EnumMap;
 
-use crate::{Action, ActionName, CooldownType, Player};
-
-pub fn play_sequence(actions_map: &EnumMap<ActionName, Action>) {
-    let mut player = Player::default();
-    player.assign_actions(actions_map);
-
-    let action_sequence: Vec<ActionName> = vec![
-        ActionName::FastBlade,
-        ActionName::RiotBlade,
-        ActionName::RoyalAuthority,
-        ActionName::FightOrFlight,
-        ActionName::Requiescat,
-        ActionName::GoringBlade,
-        ActionName::Expiacion,
-        ActionName::CircleOfScorn,
-        ActionName::Confiteor,
-        ActionName::Intervene,
-        ActionName::BladeOfFaith,
-        ActionName::Intervene,
-        ActionName::BladeOfTruth,
-        ActionName::BladeOfValor,
-        ActionName::HolySpirit,
-        ActionName::Atonement,
-        ActionName::Atonement,
-        ActionName::Atonement,
-        ActionName::FastBlade,
-        ActionName::RiotBlade,
-        ActionName::RoyalAuthority,
-        ActionName::Atonement,
-        ActionName::Expiacion,
-        ActionName::CircleOfScorn,
-        ActionName::Atonement,
-        ActionName::Atonement,
-        ActionName::HolySpirit,
-        ActionName::FastBlade,
-        ActionName::RiotBlade,
-        ActionName::RoyalAuthority,
-        ActionName::Atonement,
-        ActionName::Atonement,
-    ];
-
-    for action in &action_sequence {
-        let last_time = player.time;
-        let last_damage = player.damage;
-        player = player.apply_action(action, actions_map).unwrap();
-        if let CooldownType::OffGlobal = actions_map[*action].cooldown_type {
-            print!("  ");
+use crate::{
+    search::{press_time, StepRecord},
+    stats::Stats,
+    Action, ActionApplyError, ActionName, CooldownType, Player, Step,
+};
+
+/// One line of a rotation file.
+///
+/// ```text
+/// # comments and blank lines are ignored
+/// Fast Blade
+///   Fight or Flight     <- indented lines are oGCDs weaved after the previous GCD
+/// Riot Blade @2.5s      <- pressed exactly at 2.5 s, holding until then if needed
+/// wait 1.2s             <- idle, also accepts `1200ms`
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceLine {
+    pub line: usize,
+    pub step: Step,
+    pub weave: bool,
+    pub at: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum LineError {
+    UnknownAction(String),
+    BadTime(String),
+    WeaveMismatch(ActionName, bool),
+    TooLate(ActionName, u32, u32),
+    Apply(ActionApplyError),
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::UnknownAction(name) => write!(f, "unknown action name `{}`", name),
+            LineError::BadTime(time) => {
+                write!(
+                    f,
+                    "cannot parse time `{}`, expected e.g. 1.2s or 1200ms",
+                    time
+                )
+            }
+            LineError::WeaveMismatch(action_name, true) => write!(
+                f,
+                "{} is a GCD and cannot be indented as a weave",
+                action_name
+            ),
+            LineError::WeaveMismatch(action_name, false) => {
+                write!(f, "{} is an oGCD and must be indented", action_name)
+            }
+            LineError::TooLate(action_name, at, ready) => write!(
+                f,
+                "{} is pinned at {}ms but cannot be used before {}ms",
+                action_name, at, ready
+            ),
+            LineError::Apply(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SequenceError {
+    Io(PathBuf, io::Error),
+    Line(usize, LineError),
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            SequenceError::Line(line, e) => write!(f, "line {}: {}", line, e),
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+pub(crate) fn parse_time(text: &str) -> Result<u32, LineError> {
+    let bad_time = || LineError::BadTime(text.to_owned());
+    let (number, scale) = if let Some(number) = text.strip_suffix("ms") {
+        (number, 1.0)
+    } else if let Some(number) = text.strip_suffix('s') {
+        (number, 1000.0)
+    } else {
+        return Err(bad_time());
+    };
+    let value: f64 = number.trim().parse().map_err(|_| bad_time())?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(bad_time());
+    }
+    Ok((value * scale).round() as u32)
+}
+
+fn parse_line(text: &str) -> Result<Option<(Step, bool, Option<u32>)>, LineError> {
+    let text = text.split('#').next().unwrap();
+    let content = text.trim();
+    if content.is_empty() {
+        return Ok(None);
+    }
+    let weave = text.starts_with(char::is_whitespace);
+
+    if let Some(time) = content.strip_prefix("wait ") {
+        return Ok(Some((Step::Wait(parse_time(time.trim())?), weave, None)));
+    }
+
+    let (name, at) = match content.split_once('@') {
+        Some((name, at)) => (name.trim(), Some(parse_time(at.trim())?)),
+        None => (content, None),
+    };
+    match ActionName::from_display_name(name) {
+        Some(action_name) => Ok(Some((Step::Action(action_name), weave, at))),
+        None => Err(LineError::UnknownAction(name.to_owned())),
+    }
+}
+
+pub fn parse_sequence(text: &str) -> Result<Vec<SequenceLine>, SequenceError> {
+    let mut sequence = vec![];
+    for (index, text) in text.lines().enumerate() {
+        let line = index + 1;
+        let parsed = parse_line(text).map_err(|e| SequenceError::Line(line, e))?;
+        if let Some((step, weave, at)) = parsed {
+            sequence.push(SequenceLine {
+                line,
+                step,
+                weave,
+                at,
+            });
+        }
+    }
+    Ok(sequence)
+}
+
+pub fn load_sequence(path: &Path) -> Result<Vec<SequenceLine>, SequenceError> {
+    let text = fs::read_to_string(path).map_err(|e| SequenceError::Io(path.to_owned(), e))?;
+    parse_sequence(&text)
+}
+
+/// Plays one line, returning when its step was pressed and the player after it
+fn play_line(
+    player: &Player,
+    sequence_line: &SequenceLine,
+    actions_map: &EnumMap<ActionName, Action>,
+) -> Result<(u32, Player), LineError> {
+    let action_name = match sequence_line.step {
+        Step::Action(action_name) => action_name,
+        Step::Wait(_) => {
+            let next = player
+                .apply_step(&sequence_line.step, actions_map)
+                .map_err(LineError::Apply)?;
+            return Ok((player.time, next));
+        }
+    };
+    let action = &actions_map[action_name];
+    let is_weave = action.cooldown_type() == CooldownType::OffGlobal;
+    if is_weave != sequence_line.weave {
+        return Err(LineError::WeaveMismatch(action_name, sequence_line.weave));
+    }
+
+    let mut player = player.clone();
+    if let Some(at) = sequence_line.at {
+        if player.time < at {
+            player = player
+                .apply_step(&Step::Wait(at - player.time), actions_map)
+                .map_err(LineError::Apply)?;
+        }
+        let ready = player.time + player.ready_in(action);
+        if ready > at {
+            return Err(LineError::TooLate(action_name, at, ready));
         }
-        println!(
-            "{:?} -> time: {} (+{}), damage: {} (+{}), mp: {}, intervene: {} / {}",
-            action,
-            player.time,
-            player.time - last_time,
-            (player.damage as f64) / (player.time as f64) * 1000f64,
-            player.damage - last_damage,
-            player.mp,
-            player.action_status[ActionName::Intervene].charges,
-            player.action_status[ActionName::Intervene].cooldown,
-        );
     }
+    let time = press_time(actions_map, &player, &sequence_line.step);
+    let next = player
+        .apply_step(&sequence_line.step, actions_map)
+        .map_err(LineError::Apply)?;
+    Ok((time, next))
+}
+
+/// Plays the rotation file from a fresh player, recording when each step was pressed and what
+/// it dealt, see [`crate::report::play_text`]
+pub fn play_sequence(
+    actions_map: &EnumMap<ActionName, Action>,
+    stats: Stats,
+    action_sequence: &[SequenceLine],
+) -> Result<(Vec<StepRecord>, Player), SequenceError> {
+    let mut player = Player::new(stats, actions_map);
+    let mut records = vec![];
+    for sequence_line in action_sequence {
+        let (time, next) = play_line(&player, sequence_line, actions_map)
+            .map_err(|e| SequenceError::Line(sequence_line.line, e))?;
+        records.push(StepRecord {
+            step: sequence_line.step,
+            time,
+            damage: next.damage - player.damage,
+        });
+        player = next;
+    }
+    Ok((records, player))
 }
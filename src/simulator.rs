@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use enum_map::EnumMap;
+
+use crate::{
+    alternatives::{self, AlternativesResult},
+    checkpoint::{self, CheckpointError, CheckpointOptions, Snapshot},
+    constraints::Constraints,
+    cycle::{self, CycleOptions, CycleResult},
+    search::{search, SearchOptions, SearchResult},
+    stats::{load_stats, Stats},
+    table::{load_actions, TableError},
+    Action, ActionApplyError, ActionName, Player, Step,
+};
+
+/// An action table paired with character stats, the entry point for driving the engine from
+/// other tools
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    actions_map: EnumMap<ActionName, Action>,
+    stats: Stats,
+}
+
+impl Simulator {
+    pub fn new(actions_map: EnumMap<ActionName, Action>, stats: Stats) -> Self {
+        Simulator { actions_map, stats }
+    }
+
+    pub fn load(actions: &Path, stats: &Path) -> Result<Self, TableError> {
+        Ok(Simulator::new(load_actions(actions)?, load_stats(stats)?))
+    }
+
+    pub fn actions(&self) -> &EnumMap<ActionName, Action> {
+        &self.actions_map
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// A fresh player at time 0 with every cooldown ready
+    pub fn player(&self) -> Player {
+        Player::new(self.stats, &self.actions_map)
+    }
+
+    pub fn apply(&self, player: &Player, step: Step) -> Result<Player, ActionApplyError> {
+        player.apply_step(&step, &self.actions_map)
+    }
+
+    /// Plays the steps in order from a fresh player
+    pub fn play(&self, steps: &[Step]) -> Result<Player, ActionApplyError> {
+        steps
+            .iter()
+            .try_fold(self.player(), |player, step| self.apply(&player, *step))
+    }
+
+    pub fn search(&self, options: &SearchOptions) -> SearchResult {
+        search(
+            &self.actions_map,
+            self.stats,
+            options,
+            &Constraints::default(),
+            None,
+        )
+        .expect("only saving a checkpoint fails")
+        .expect("without constraints the empty rotation always counts")
+    }
+
+    /// Like [`Simulator::search`], keeping only rotations that follow the constraints. Returns
+    /// `None` if no rotation does.
+    pub fn search_constrained(
+        &self,
+        options: &SearchOptions,
+        constraints: &Constraints,
+    ) -> Option<SearchResult> {
+        search(&self.actions_map, self.stats, options, constraints, None)
+            .expect("only saving a checkpoint fails")
+    }
+
+    /// Like [`Simulator::search_constrained`], saving its progress as it goes and possibly
+    /// continuing an earlier run, see [`search`]. Fails if a checkpoint cannot be saved.
+    pub fn search_checkpointed(
+        &self,
+        options: &SearchOptions,
+        constraints: &Constraints,
+        checkpoint: CheckpointOptions,
+    ) -> Result<Option<SearchResult>, CheckpointError> {
+        search(
+            &self.actions_map,
+            self.stats,
+            options,
+            constraints,
+            Some(checkpoint),
+        )
+    }
+
+    /// Reads a checkpoint saved by a search with the same options and constraints, see
+    /// [`checkpoint::load`]
+    pub fn load_checkpoint(
+        &self,
+        path: &Path,
+        options: &SearchOptions,
+        constraints: &Constraints,
+    ) -> Result<Snapshot, CheckpointError> {
+        checkpoint::load(path, &self.actions_map, options, constraints, self.stats)
+    }
+
+    /// The `count` best distinct rotations that follow the constraints, see
+    /// [`alternatives::search`]
+    pub fn alternatives(
+        &self,
+        options: &SearchOptions,
+        constraints: &Constraints,
+        count: usize,
+    ) -> AlternativesResult {
+        alternatives::search(&self.actions_map, self.stats, options, constraints, count)
+    }
+
+    /// Best repeating rotation found, see [`cycle::search`]
+    pub fn cycle(&self, options: &CycleOptions) -> Option<CycleResult> {
+        cycle::search(&self.actions_map, self.stats, options)
+    }
+}
@@ -0,0 +1,138 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{status::Buffs, table::TableError, AttackType, GLOBAL_COOLDOWN_GROUP};
+
+// Level 90 modifiers
+const LEVEL_MAIN: u64 = 390;
+const LEVEL_SUB: u64 = 400;
+const LEVEL_DIV: u64 = 1900;
+// Strength job modifier of Paladin, in percent
+const JOB_MOD: u64 = 100;
+// Tanks get a flatter attack power curve than other roles
+const TANK_AP_MOD: u64 = 156;
+const DIRECT_HIT_MULTIPLIER: f64 = 1.25;
+// Potencies in the action table are stored in tenths
+const POTENCY_SCALE: u64 = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Stats {
+    pub weapon_damage: u32,
+    pub main_stat: u32,
+    pub determination: u32,
+    pub tenacity: u32,
+    pub critical_hit: u32,
+    pub direct_hit: u32,
+    pub skill_speed: u32,
+    pub spell_speed: u32,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            weapon_damage: 132,
+            main_stat: 3330,
+            determination: 2047,
+            tenacity: 751,
+            critical_hit: 2576,
+            direct_hit: 1180,
+            skill_speed: 400,
+            spell_speed: 400,
+        }
+    }
+}
+
+impl Stats {
+    fn sub_stat(value: u32) -> u64 {
+        (value as u64).saturating_sub(LEVEL_SUB)
+    }
+
+    pub fn weapon_damage_multiplier(&self) -> u64 {
+        LEVEL_MAIN * JOB_MOD / 1000 + self.weapon_damage as u64
+    }
+
+    pub fn attack_power_multiplier(&self) -> u64 {
+        TANK_AP_MOD * (self.main_stat as u64).saturating_sub(LEVEL_MAIN) / LEVEL_MAIN + 100
+    }
+
+    pub fn determination_multiplier(&self) -> u64 {
+        140 * (self.determination as u64).saturating_sub(LEVEL_MAIN) / LEVEL_DIV + 1000
+    }
+
+    pub fn tenacity_multiplier(&self) -> u64 {
+        100 * Stats::sub_stat(self.tenacity) / LEVEL_DIV + 1000
+    }
+
+    /// Critical hit rate, in per mille
+    pub fn critical_hit_rate(&self) -> u64 {
+        200 * Stats::sub_stat(self.critical_hit) / LEVEL_DIV + 50
+    }
+
+    /// Damage multiplier of a critical hit, in per mille
+    pub fn critical_hit_multiplier(&self) -> u64 {
+        200 * Stats::sub_stat(self.critical_hit) / LEVEL_DIV + 1400
+    }
+
+    /// Direct hit rate, in per mille
+    pub fn direct_hit_rate(&self) -> u64 {
+        550 * Stats::sub_stat(self.direct_hit) / LEVEL_DIV
+    }
+
+    /// Recast or cast time multiplier for the given speed stat, in per mille
+    pub fn speed_multiplier(speed: u32) -> u64 {
+        1000 - 130 * Stats::sub_stat(speed) / LEVEL_DIV
+    }
+
+    /// Adjusts a base recast or cast time by skill speed for weaponskills and spell speed for
+    /// spells, then by haste, truncated to centiseconds like the game does
+    pub fn recast(&self, base: u32, attack_type: AttackType, haste: u64) -> u32 {
+        let speed = match attack_type {
+            AttackType::Weaponskill => self.skill_speed,
+            AttackType::Spell => self.spell_speed,
+            AttackType::Ability => return base,
+        };
+        let recast = base as u64 * Stats::speed_multiplier(speed) / 1000;
+        (recast * (100 - haste.min(100)) / 100 / 10 * 10) as u32
+    }
+
+    /// Recast a use puts on the timer of a cooldown group. Speed and haste only shorten the GCD,
+    /// never an action's own cooldown.
+    pub fn group_recast(&self, group: u32, base: u32, attack_type: AttackType, haste: u64) -> u32 {
+        if group == GLOBAL_COOLDOWN_GROUP {
+            self.recast(base, attack_type, haste)
+        } else {
+            base
+        }
+    }
+
+    /// Damage of a non-critical, non-direct hit before any buffs
+    pub fn base_damage(&self, potency: u32) -> u64 {
+        let d1 = potency as u64 * self.attack_power_multiplier() * self.determination_multiplier()
+            / POTENCY_SCALE
+            / 100
+            / 1000;
+        d1 * self.tenacity_multiplier() / 1000 * self.weapon_damage_multiplier() / 100
+    }
+
+    /// Average damage of a hit under the given buffs, weighting critical and direct hits by their
+    /// rates
+    pub fn expected_damage(&self, potency: u32, buffs: &Buffs) -> u32 {
+        let critical_hit_rate =
+            ((self.critical_hit_rate() + buffs.critical_hit_rate) as f64 / 1000.0).min(1.0);
+        let critical_hit_multiplier = self.critical_hit_multiplier() as f64 / 1000.0;
+        let direct_hit_rate =
+            ((self.direct_hit_rate() + buffs.direct_hit_rate) as f64 / 1000.0).min(1.0);
+        let damage = self.base_damage(potency) as f64
+            * buffs.damage
+            * (1.0 + critical_hit_rate * (critical_hit_multiplier - 1.0))
+            * (1.0 + direct_hit_rate * (DIRECT_HIT_MULTIPLIER - 1.0));
+        damage.round() as u32
+    }
+}
+
+pub fn load_stats(path: &Path) -> Result<Stats, TableError> {
+    let text = fs::read_to_string(path).map_err(|e| TableError::Io(path.to_owned(), e))?;
+    serde_json::from_str(&text).map_err(TableError::Parse)
+}
@@ -0,0 +1,152 @@
+use enum_map::Enum;
+use serde::{Deserialize, Serialize};
+
+use crate::AttackType;
+
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Enum, Serialize, Deserialize,
+)]
+pub enum StatusName {
+    FightOrFlight,
+    Requiescat,
+    SwordOath,
+    DivineMight,
+    ConfiteorReady,
+    CircleOfScorn,
+    GoringBlade,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Default, Serialize, Deserialize,
+)]
+pub enum Modifier {
+    #[default]
+    None,
+    /// Increases all damage dealt, in percent
+    DamageUp(u32),
+    /// Increases damage dealt by weaponskills and abilities, in percent
+    PhysicalDamageUp(u32),
+    /// Increases critical hit rate, in percentage points
+    CriticalHitUp(u32),
+    /// Increases direct hit rate, in percentage points
+    DirectHitUp(u32),
+    /// Reduces recast and cast times of weaponskills and spells, in percent
+    Haste(u32),
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusApplication {
+    pub name: StatusName,
+    pub duration: u32,
+    #[serde(default = "one")]
+    pub stacks: u32,
+    #[serde(default)]
+    pub modifier: Modifier,
+    /// Damage over time potency dealt every server tick
+    #[serde(default)]
+    pub potency: u32,
+    /// Only applied when the action lands as part of its combo
+    #[serde(default)]
+    pub combo: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Default)]
+pub struct StatusEffect {
+    pub duration: u32,
+    pub stacks: u32,
+    pub modifier: Modifier,
+    pub tick_damage: u32,
+}
+
+impl StatusEffect {
+    pub const KEY_LEN: usize = 3;
+
+    /// The fields of the effect that change during a fight, for a player state key. The
+    /// modifier is left out, since the table gives every status a single one.
+    pub fn key(&self) -> [u32; StatusEffect::KEY_LEN] {
+        [self.duration, self.stacks, self.tick_damage]
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.duration > 0 && self.stacks > 0
+    }
+
+    pub fn apply(&mut self, application: &StatusApplication, tick_damage: u32) {
+        self.duration = application.duration;
+        self.stacks = application.stacks;
+        self.modifier = application.modifier;
+        self.tick_damage = tick_damage;
+    }
+
+    pub fn consume(&mut self) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        self.stacks -= 1;
+        if self.stacks == 0 {
+            *self = StatusEffect::default();
+        }
+        true
+    }
+
+    pub fn tick(&mut self, time: u32) {
+        self.duration = self.duration.saturating_sub(time);
+        if self.duration == 0 {
+            // Forget the modifier and tick damage too, so an expired status equals one never had
+            *self = StatusEffect::default();
+        }
+    }
+}
+
+/// All active modifiers folded together for a single hit or recast
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Buffs {
+    pub damage: f64,
+    /// Bonus critical hit rate, in per mille
+    pub critical_hit_rate: u64,
+    /// Bonus direct hit rate, in per mille
+    pub direct_hit_rate: u64,
+    /// Recast and cast reduction, in percent
+    pub haste: u64,
+}
+
+impl Default for Buffs {
+    fn default() -> Self {
+        Buffs {
+            damage: 1.0,
+            critical_hit_rate: 0,
+            direct_hit_rate: 0,
+            haste: 0,
+        }
+    }
+}
+
+impl Buffs {
+    pub fn add(&mut self, modifier: Modifier, attack_type: AttackType) {
+        match modifier {
+            Modifier::None => {}
+            Modifier::DamageUp(percent) => {
+                self.damage *= 1.0 + percent as f64 / 100.0;
+            }
+            Modifier::PhysicalDamageUp(percent) => {
+                if attack_type != AttackType::Spell {
+                    self.damage *= 1.0 + percent as f64 / 100.0;
+                }
+            }
+            Modifier::CriticalHitUp(points) => {
+                self.critical_hit_rate += points as u64 * 10;
+            }
+            Modifier::DirectHitUp(points) => {
+                self.direct_hit_rate += points as u64 * 10;
+            }
+            Modifier::Haste(percent) => {
+                self.haste += percent as u64;
+            }
+        }
+    }
+}
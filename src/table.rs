@@ -0,0 +1,310 @@
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use enum_map::{Enum, EnumMap};
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{
+    status::{Modifier, StatusApplication, StatusName},
+    Action, ActionName, AttackType, ComboEffect, ANIMATION_LOCK, GLOBAL_COOLDOWN,
+};
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(PathBuf, io::Error),
+    Parse(serde_json::Error),
+    UnknownAction(String),
+    MissingAction(ActionName),
+    Field(ActionName, serde_json::Error),
+    Invalid(ActionName, &'static str),
+    GroupConflict(u32, ActionName, ActionName),
+    StatusConflict(StatusName, ActionName, ActionName),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            TableError::Parse(e) => write!(f, "malformed JSON: {}", e),
+            TableError::UnknownAction(name) => write!(f, "unknown action name `{}`", name),
+            TableError::MissingAction(name) => write!(f, "action {:?} is missing", name),
+            TableError::Field(name, e) => write!(f, "action {:?}: {}", name, e),
+            TableError::Invalid(name, reason) => write!(f, "action {:?}: {}", name, reason),
+            TableError::GroupConflict(group, first, second) => write!(
+                f,
+                "actions {:?} and {:?} share cooldown group {} but disagree on recast or max_charges",
+                first, second, group
+            ),
+            TableError::StatusConflict(status, first, second) => write!(
+                f,
+                "actions {:?} and {:?} apply status {:?} with different modifiers",
+                first, second, status
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActionEntry {
+    attack_type: AttackType,
+    cooldown_group: u32,
+    #[serde(default)]
+    additional_cooldown_group: u32,
+    cast: u32,
+    recast: u32,
+    #[serde(default)]
+    mp_cost: u32,
+    #[serde(default)]
+    mp_restore: u32,
+    potency: u32,
+    #[serde(default)]
+    combo_potency: u32,
+    #[serde(default)]
+    secondary_potency: u32,
+    #[serde(default)]
+    tertiary_potency: u32,
+    max_charges: u32,
+    #[serde(default)]
+    statuses: Vec<StatusApplication>,
+    #[serde(default)]
+    combo_from: Option<ActionName>,
+    #[serde(default)]
+    combo_required: bool,
+    #[serde(default)]
+    combo_effect: ComboEffect,
+}
+
+impl ActionEntry {
+    fn into_action(self, name: ActionName) -> Result<Action, TableError> {
+        if self.cooldown_group == 0 {
+            return Err(TableError::Invalid(name, "cooldown_group must be positive"));
+        }
+        if self.max_charges == 0 {
+            return Err(TableError::Invalid(name, "max_charges must be at least 1"));
+        }
+        if self.recast == 0 {
+            return Err(TableError::Invalid(name, "recast must be positive"));
+        }
+        if self.cast == 0 {
+            return Err(TableError::Invalid(name, "cast must be positive"));
+        }
+        if self.combo_from == Some(ActionName::None) {
+            return Err(TableError::Invalid(name, "combo_from cannot be None"));
+        }
+        if self.combo_required && self.combo_from.is_none() {
+            return Err(TableError::Invalid(
+                name,
+                "combo_required is set without combo_from",
+            ));
+        }
+        for application in &self.statuses {
+            if application.duration == 0 {
+                return Err(TableError::Invalid(
+                    name,
+                    "status duration must be positive",
+                ));
+            }
+            if application.stacks == 0 {
+                return Err(TableError::Invalid(
+                    name,
+                    "status stacks must be at least 1",
+                ));
+            }
+        }
+        Ok(Action {
+            name,
+            attack_type: self.attack_type,
+            cooldown_group: self.cooldown_group,
+            additional_cooldown_group: self.additional_cooldown_group,
+            cast: self.cast,
+            recast: self.recast,
+            mp_cost: self.mp_cost,
+            mp_restore: self.mp_restore,
+            potency: self.potency,
+            combo_potency: self.combo_potency,
+            secondary_potency: self.secondary_potency,
+            tertiary_potency: self.tertiary_potency,
+            max_charges: self.max_charges,
+            statuses: self.statuses,
+            combo_from: self.combo_from,
+            combo_required: self.combo_required,
+            combo_effect: self.combo_effect,
+            timers: [None; 2],
+        })
+    }
+}
+
+impl From<&Action> for ActionEntry {
+    fn from(action: &Action) -> Self {
+        ActionEntry {
+            attack_type: action.attack_type,
+            cooldown_group: action.cooldown_group,
+            additional_cooldown_group: action.additional_cooldown_group,
+            cast: action.cast,
+            recast: action.recast,
+            mp_cost: action.mp_cost,
+            mp_restore: action.mp_restore,
+            potency: action.potency,
+            combo_potency: action.combo_potency,
+            secondary_potency: action.secondary_potency,
+            tertiary_potency: action.tertiary_potency,
+            max_charges: action.max_charges,
+            statuses: action.statuses.clone(),
+            combo_from: action.combo_from,
+            combo_required: action.combo_required,
+            combo_effect: action.combo_effect,
+        }
+    }
+}
+
+struct ActionTable<'a>(&'a EnumMap<ActionName, Action>);
+
+impl Serialize for ActionTable<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(ActionName::LENGTH - 1))?;
+        for (action_name, action) in self.0 {
+            if action_name != ActionName::None {
+                map.serialize_entry(&format!("{:?}", action_name), &ActionEntry::from(action))?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl ActionName {
+    pub fn parse(name: &str) -> Option<ActionName> {
+        (0..ActionName::LENGTH)
+            .map(ActionName::from_usize)
+            .find(|action_name| format!("{:?}", action_name) == name)
+    }
+}
+
+fn none_action() -> Action {
+    Action {
+        name: ActionName::None,
+        attack_type: AttackType::Ability,
+        cooldown_group: 0,
+        additional_cooldown_group: 0,
+        cast: ANIMATION_LOCK,
+        recast: GLOBAL_COOLDOWN,
+        mp_cost: 0,
+        mp_restore: 0,
+        potency: 0,
+        combo_potency: 0,
+        secondary_potency: 0,
+        tertiary_potency: 0,
+        max_charges: 1,
+        statuses: vec![],
+        combo_from: None,
+        combo_required: false,
+        combo_effect: ComboEffect::Keep,
+        timers: [None; 2],
+    }
+}
+
+pub fn parse_actions(text: &str) -> Result<EnumMap<ActionName, Action>, TableError> {
+    let entries: Map<String, Value> = serde_json::from_str(text).map_err(TableError::Parse)?;
+
+    let mut actions: EnumMap<ActionName, Option<Action>> = EnumMap::default();
+    actions[ActionName::None] = Some(none_action());
+    for (key, value) in entries {
+        let name = match ActionName::parse(&key) {
+            Some(ActionName::None) | None => return Err(TableError::UnknownAction(key)),
+            Some(name) => name,
+        };
+        let entry: ActionEntry =
+            serde_json::from_value(value).map_err(|e| TableError::Field(name, e))?;
+        actions[name] = Some(entry.into_action(name)?);
+    }
+
+    if let Some((name, _)) = actions.iter().find(|(_, action)| action.is_none()) {
+        return Err(TableError::MissingAction(name));
+    }
+    let mut actions = actions.map(|_, action| action.unwrap());
+    check_groups(&actions)?;
+    check_statuses(&actions)?;
+    index_timers(&mut actions);
+    Ok(actions)
+}
+
+fn check_groups(actions: &EnumMap<ActionName, Action>) -> Result<(), TableError> {
+    let mut owners: HashMap<u32, &Action> = HashMap::new();
+    for action in actions.values().filter(|action| action.cooldown_group != 0) {
+        match owners.get(&action.cooldown_group) {
+            Some(owner)
+                if owner.recast != action.recast || owner.max_charges != action.max_charges =>
+            {
+                return Err(TableError::GroupConflict(
+                    action.cooldown_group,
+                    owner.name,
+                    action.name,
+                ));
+            }
+            Some(_) => {}
+            None => {
+                owners.insert(action.cooldown_group, action);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Player state keys leave out status modifiers, so each status may only have one
+fn check_statuses(actions: &EnumMap<ActionName, Action>) -> Result<(), TableError> {
+    let mut owners: EnumMap<StatusName, Option<(&Action, Modifier)>> = EnumMap::default();
+    for action in actions.values() {
+        for application in &action.statuses {
+            match owners[application.name] {
+                Some((owner, modifier)) if modifier != application.modifier => {
+                    return Err(TableError::StatusConflict(
+                        application.name,
+                        owner.name,
+                        action.name,
+                    ));
+                }
+                Some(_) => {}
+                None => owners[application.name] = Some((action, application.modifier)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Numbers the cooldown groups some action owns densely, so players keep their recast timers in
+/// a slice. Additional groups nobody owns get no timer.
+pub(crate) fn index_timers(actions: &mut EnumMap<ActionName, Action>) {
+    let mut indices: HashMap<u32, usize> = HashMap::new();
+    for action in actions.values().filter(|action| action.cooldown_group != 0) {
+        let next = indices.len();
+        indices.entry(action.cooldown_group).or_insert(next);
+    }
+    for action in actions.values_mut() {
+        action.timers = [action.cooldown_group, action.additional_cooldown_group]
+            .map(|group| indices.get(&group).copied());
+    }
+}
+
+/// Number of recast timers `index_timers` gave out
+pub(crate) fn timer_count(actions: &EnumMap<ActionName, Action>) -> usize {
+    actions
+        .values()
+        .filter_map(|action| action.timers[0])
+        .max()
+        .map_or(0, |index| index + 1)
+}
+
+pub fn load_actions(path: &Path) -> Result<EnumMap<ActionName, Action>, TableError> {
+    let text = fs::read_to_string(path).map_err(|e| TableError::Io(path.to_owned(), e))?;
+    parse_actions(&text)
+}
+
+pub fn actions_to_json(actions_map: &EnumMap<ActionName, Action>) -> String {
+    serde_json::to_string_pretty(&ActionTable(actions_map)).unwrap()
+}
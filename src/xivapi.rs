@@ -0,0 +1,197 @@
+use std::{fmt, fs, path::Path};
+
+use enum_map::{Enum, EnumMap};
+use serde::Deserialize;
+
+use crate::{
+    table::{self, TableError},
+    Action, ActionName, ANIMATION_LOCK,
+};
+
+const COST_TYPE_MP: u32 = 3;
+const MP_PER_COST_VALUE: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Row {
+    pub name: String,
+    #[serde(rename = "ID")]
+    pub id: u32,
+    pub cooldown_group: u32,
+    pub additional_cooldown_group: u32,
+    pub cast100ms: u32,
+    pub recast100ms: u32,
+    pub primary_cost_type: u32,
+    pub primary_cost_value: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Snapshot {
+    pub data: Vec<Row>,
+}
+
+impl Row {
+    pub fn cast(&self) -> u32 {
+        if self.cast100ms == 0 {
+            ANIMATION_LOCK
+        } else {
+            self.cast100ms * 100
+        }
+    }
+
+    pub fn recast(&self) -> u32 {
+        self.recast100ms * 100
+    }
+
+    pub fn mp_cost(&self) -> u32 {
+        if self.primary_cost_type == COST_TYPE_MP {
+            self.primary_cost_value * MP_PER_COST_VALUE
+        } else {
+            0
+        }
+    }
+}
+
+impl ActionName {
+    pub fn from_display_name(name: &str) -> Option<ActionName> {
+        let name: String = name.split_whitespace().collect();
+        (0..ActionName::LENGTH)
+            .map(ActionName::from_usize)
+            .filter(|action_name| *action_name != ActionName::None)
+            .find(|action_name| format!("{:?}", action_name).eq_ignore_ascii_case(&name))
+    }
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    pub action: ActionName,
+    pub field: &'static str,
+    pub table: String,
+    pub snapshot: String,
+}
+
+#[derive(Debug)]
+pub struct ImportReport {
+    pub actions_map: EnumMap<ActionName, Action>,
+    pub unmapped: Vec<(u32, String)>,
+    pub missing: Vec<ActionName>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} snapshot actions have no ActionName:",
+            self.unmapped.len()
+        )?;
+        for (id, name) in &self.unmapped {
+            writeln!(f, "  {} (#{})", name, id)?;
+        }
+        writeln!(f, "{} actions are not in the snapshot:", self.missing.len())?;
+        for action in &self.missing {
+            writeln!(f, "  {:?}", action)?;
+        }
+        writeln!(
+            f,
+            "{} fields disagree with the table:",
+            self.mismatches.len()
+        )?;
+        for mismatch in &self.mismatches {
+            writeln!(
+                f,
+                "  {:?}.{}: table={}, snapshot={}",
+                mismatch.action, mismatch.field, mismatch.table, mismatch.snapshot
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn compare<T: PartialEq + fmt::Debug>(
+    mismatches: &mut Vec<Mismatch>,
+    action: ActionName,
+    field: &'static str,
+    table: T,
+    snapshot: T,
+) {
+    if table != snapshot {
+        mismatches.push(Mismatch {
+            action,
+            field,
+            table: format!("{:?}", table),
+            snapshot: format!("{:?}", snapshot),
+        });
+    }
+}
+
+pub fn load_snapshot(path: &Path) -> Result<Snapshot, TableError> {
+    let text = fs::read_to_string(path).map_err(|e| TableError::Io(path.to_owned(), e))?;
+    serde_json::from_str(&text).map_err(TableError::Parse)
+}
+
+pub fn import(snapshot: &Snapshot, actions_map: &EnumMap<ActionName, Action>) -> ImportReport {
+    let mut report = ImportReport {
+        actions_map: actions_map.clone(),
+        unmapped: vec![],
+        missing: vec![],
+        mismatches: vec![],
+    };
+    let mut seen: EnumMap<ActionName, bool> = EnumMap::default();
+
+    for row in &snapshot.data {
+        let action_name = match ActionName::from_display_name(&row.name) {
+            Some(action_name) => action_name,
+            None => {
+                report.unmapped.push((row.id, row.name.clone()));
+                continue;
+            }
+        };
+        seen[action_name] = true;
+
+        let mismatches = &mut report.mismatches;
+        let action = &mut report.actions_map[action_name];
+        compare(mismatches, action_name, "cast", action.cast, row.cast());
+        compare(
+            mismatches,
+            action_name,
+            "recast",
+            action.recast,
+            row.recast(),
+        );
+        compare(
+            mismatches,
+            action_name,
+            "mp_cost",
+            action.mp_cost,
+            row.mp_cost(),
+        );
+        compare(
+            mismatches,
+            action_name,
+            "cooldown_group",
+            action.cooldown_group,
+            row.cooldown_group,
+        );
+        compare(
+            mismatches,
+            action_name,
+            "additional_cooldown_group",
+            action.additional_cooldown_group,
+            row.additional_cooldown_group,
+        );
+        action.cast = row.cast();
+        action.recast = row.recast();
+        action.mp_cost = row.mp_cost();
+        action.cooldown_group = row.cooldown_group;
+        action.additional_cooldown_group = row.additional_cooldown_group;
+    }
+
+    report.missing = seen
+        .iter()
+        .filter(|(action_name, seen)| *action_name != ActionName::None && !**seen)
+        .map(|(action_name, _)| action_name)
+        .collect();
+    table::index_timers(&mut report.actions_map);
+    report
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{moves, press_time, simulator};
+use ffxiv_rotation::{Constraints, Objective, Player, SearchOptions, Simulator, Step, StepRecord};
+
+/// Collects every rotation that cannot be extended before the horizon, keyed by the actions it
+/// presses and when
+fn brute_force(
+    simulator: &Simulator,
+    player: &Player,
+    horizon: u32,
+    pressed: &mut Vec<(Step, u32)>,
+    ends: &mut HashMap<Vec<(Step, u32)>, u32>,
+) {
+    let mut extended = false;
+    for step in moves(player) {
+        let next = match simulator.apply(player, step) {
+            Ok(next) if next.time() <= horizon => next,
+            _ => continue,
+        };
+        extended = true;
+        match step {
+            Step::Action(_) => {
+                pressed.push((step, press_time(simulator, player, step)));
+                brute_force(simulator, &next, horizon, pressed, ends);
+                pressed.pop();
+            }
+            Step::Wait(_) => brute_force(simulator, &next, horizon, pressed, ends),
+        }
+    }
+    if !extended {
+        let damage = ends.entry(pressed.clone()).or_default();
+        *damage = player.damage().max(*damage);
+    }
+}
+
+fn pressed(steps: &[StepRecord]) -> Vec<(Step, u32)> {
+    steps
+        .iter()
+        .filter(|record| matches!(record.step, Step::Action(_)))
+        .map(|record| (record.step, record.time))
+        .collect()
+}
+
+#[test]
+fn top_rotations_match_brute_force() {
+    let simulator = simulator();
+    let count = 8;
+    for horizon in [2500, 3300] {
+        let mut ends = HashMap::new();
+        brute_force(
+            &simulator,
+            &simulator.player(),
+            horizon,
+            &mut vec![],
+            &mut ends,
+        );
+        let mut expected: Vec<u32> = ends.values().copied().collect();
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.truncate(count);
+
+        let options = SearchOptions {
+            horizon,
+            ..Default::default()
+        };
+        let result = simulator.alternatives(&options, &Constraints::default(), count);
+        let values: Vec<u32> = result.alternatives.iter().map(|a| a.value).collect();
+        assert_eq!(values, expected, "horizon {}", horizon);
+        assert_eq!(values[0], simulator.search(&options).value);
+
+        for alternative in &result.alternatives {
+            let key = pressed(&alternative.steps);
+            assert_eq!(ends.get(&key), Some(&alternative.value));
+        }
+    }
+}
+
+#[test]
+fn alternatives_are_distinct_and_diverge_from_the_best() {
+    let simulator = simulator();
+    let options = SearchOptions {
+        horizon: 4000,
+        objective: Objective::Damage,
+        ..Default::default()
+    };
+    let result = simulator.alternatives(&options, &Constraints::default(), 5);
+    assert_eq!(result.alternatives.len(), 5);
+
+    let best = pressed(&result.alternatives[0].steps);
+    assert_eq!(result.alternatives[0].diverges_at, None);
+    assert_eq!(result.alternatives[0].shared, best.len());
+    let mut seen = vec![];
+    for alternative in &result.alternatives {
+        let steps: Vec<Step> = alternative.steps.iter().map(|record| record.step).collect();
+        let player = simulator.play(&steps).unwrap();
+        assert_eq!(player.damage(), alternative.damage);
+        assert!(player.time() <= options.horizon);
+
+        let ours = pressed(&alternative.steps);
+        assert!(!seen.contains(&ours));
+        seen.push(ours.clone());
+        if let Some(time) = alternative.diverges_at {
+            let shared = alternative.shared;
+            assert_eq!(ours[..shared], best[..shared]);
+            assert_ne!(ours.get(shared), best.get(shared));
+            let first = [ours.get(shared), best.get(shared)]
+                .into_iter()
+                .flatten()
+                .map(|(_, time)| *time)
+                .min();
+            assert_eq!(first, Some(time));
+        }
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{
+    status::StatusName, ActionApplyError, ActionName, ApplyErrorKind, Player, Simulator, Step,
+};
+
+fn apply_error(
+    simulator: &Simulator,
+    player: &Player,
+    action_name: ActionName,
+) -> ActionApplyError {
+    simulator
+        .apply(player, Step::Action(action_name))
+        .unwrap_err()
+}
+
+#[test]
+fn combo_and_status_requirements() {
+    let simulator = simulator();
+    let error = apply_error(&simulator, &simulator.player(), ActionName::BladeOfFaith);
+    assert!(matches!(
+        error.kind,
+        ApplyErrorKind::ComboRequired {
+            required: ActionName::Confiteor,
+            current: ActionName::None
+        }
+    ));
+    assert_eq!(error.action, ActionName::BladeOfFaith);
+    assert_eq!(
+        error.to_string(),
+        "at 0.0s: Blade of Faith requires Confiteor combo, current combo is None"
+    );
+
+    let error = apply_error(&simulator, &simulator.player(), ActionName::Atonement);
+    assert!(matches!(
+        error.kind,
+        ApplyErrorKind::StatusRequired(StatusName::SwordOath)
+    ));
+    assert_eq!(
+        error.to_string(),
+        "at 0.0s: Atonement requires SwordOath, which is not active"
+    );
+}
+
+#[test]
+fn cooldowns_report_what_is_left() {
+    let simulator = simulator();
+    let player = simulator
+        .play(&[Step::Action(ActionName::FightOrFlight)])
+        .unwrap();
+    let error = apply_error(&simulator, &player, ActionName::FightOrFlight);
+    assert_eq!(error.time, player.time());
+    assert!(matches!(
+        error.kind,
+        ApplyErrorKind::OnCooldown {
+            remaining,
+            charges: 0,
+            max_charges: 1
+        } if remaining == 60000 - player.time()
+    ));
+    assert_eq!(
+        error.to_string(),
+        "at 0.8s: Fight or Flight on cooldown for 59200 ms, charges 0/1"
+    );
+}
+
+#[test]
+fn casting_without_mp_fails() {
+    let simulator = simulator();
+    let mut player = simulator.player();
+    let error = loop {
+        match simulator.apply(&player, Step::Action(ActionName::HolySpirit)) {
+            Ok(next) => player = next,
+            Err(error) => break error,
+        }
+    };
+    assert!(matches!(
+        error.kind,
+        ApplyErrorKind::MpNotEnough {
+            required: 1000,
+            available,
+        } if available < 1000
+    ));
+}
+
+#[test]
+fn none_is_not_an_action() {
+    let simulator = simulator();
+    let error = apply_error(&simulator, &simulator.player(), ActionName::None);
+    assert!(matches!(error.kind, ApplyErrorKind::NoneAction));
+    assert_eq!(error.to_string(), "at 0.0s: None is not an action");
+}
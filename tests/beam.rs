@@ -0,0 +1,66 @@
+mod common;
+
+use common::{filler, simulator, TEN_MINUTES};
+use ffxiv_rotation::{BeamScore, Objective, SearchOptions, Step, Strategy};
+
+#[test]
+fn parses_beam_strategies() {
+    assert_eq!(
+        "beam:100".parse(),
+        Ok(Strategy::Beam {
+            width: 100,
+            score: BeamScore::Bound
+        })
+    );
+    assert_eq!(
+        "beam:8:rate".parse(),
+        Ok(Strategy::Beam {
+            width: 8,
+            score: BeamScore::Rate
+        })
+    );
+    assert!("beam".parse::<Strategy>().is_err());
+    assert!("beam:0".parse::<Strategy>().is_err());
+    assert!("beam:8:fast".parse::<Strategy>().is_err());
+    assert!("beam:8:rate:1".parse::<Strategy>().is_err());
+}
+
+#[test]
+fn wide_beam_finds_the_short_optimum() {
+    let simulator = simulator();
+    for horizon in [2500, 4000] {
+        let exact = simulator.search(&SearchOptions {
+            horizon,
+            ..Default::default()
+        });
+        let beam = simulator.search(&SearchOptions {
+            horizon,
+            strategy: Strategy::Beam {
+                width: 100_000,
+                score: BeamScore::Bound,
+            },
+            ..Default::default()
+        });
+        assert_eq!(beam.value, exact.value, "horizon {}", horizon);
+    }
+}
+
+#[test]
+fn ten_minute_beam_beats_the_filler() {
+    let simulator = simulator();
+    let filler = filler(&simulator, 0, TEN_MINUTES).damage();
+    for score in [BeamScore::Value, BeamScore::Rate, BeamScore::Bound] {
+        let options = SearchOptions {
+            horizon: TEN_MINUTES,
+            objective: Objective::Damage,
+            strategy: Strategy::Beam { width: 10, score },
+        };
+        let result = simulator.search(&options);
+        let steps: Vec<Step> = result.steps.iter().map(|record| record.step).collect();
+        let player = simulator.play(&steps).unwrap();
+        assert_eq!(player.damage(), result.damage);
+        assert_eq!(result.value, result.damage);
+        assert!(player.time() <= TEN_MINUTES);
+        assert!(result.damage > filler, "{:?}", score);
+    }
+}
@@ -0,0 +1,293 @@
+mod common;
+
+use std::{fs, path::Path, path::PathBuf, time::Duration};
+
+use common::simulator;
+use ffxiv_rotation::{
+    checkpoint::{best_path, CheckpointError},
+    ActionName, CheckpointOptions, Constraints, Objective, SearchOptions, Simulator, Step,
+    Strategy,
+};
+
+/// A checkpoint file of its own for each test, since tests run in parallel
+fn checkpoint_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ffxiv-rotation-{}-{}.ck", std::process::id(), name))
+}
+
+#[test]
+fn resumed_search_finishes_the_same() {
+    let simulator = simulator();
+    let constraints = Constraints {
+        prefix: vec![Step::Action(ActionName::FastBlade)],
+        ..Default::default()
+    };
+    for (name, options) in [
+        (
+            "damage",
+            SearchOptions {
+                horizon: 3300,
+                ..Default::default()
+            },
+        ),
+        (
+            "bound",
+            SearchOptions {
+                horizon: 4000,
+                strategy: Strategy::Bound,
+                ..Default::default()
+            },
+        ),
+        (
+            "time-to",
+            SearchOptions {
+                horizon: 6000,
+                objective: Objective::TimeTo(50000),
+                ..Default::default()
+            },
+        ),
+    ] {
+        let path = checkpoint_path(name);
+        let expected = simulator
+            .search_constrained(&options, &constraints)
+            .unwrap();
+
+        // Saving before every expansion leaves the snapshot taken just before the last one
+        let saved = simulator
+            .search_checkpointed(
+                &options,
+                &constraints,
+                CheckpointOptions {
+                    path: path.clone(),
+                    interval: Duration::ZERO,
+                    resume: None,
+                },
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.steps, expected.steps, "{}", name);
+        let best = fs::read_to_string(best_path(&path)).unwrap();
+        assert!(best.contains("score"), "{}", name);
+
+        let snapshot = simulator
+            .load_checkpoint(&path, &options, &constraints)
+            .unwrap();
+        let resumed = simulator
+            .search_checkpointed(
+                &options,
+                &constraints,
+                CheckpointOptions {
+                    path: path.clone(),
+                    interval: Duration::MAX,
+                    resume: Some(snapshot),
+                },
+            )
+            .unwrap()
+            .unwrap();
+        // Ties between queued states may break the other way after the heap is rebuilt
+        assert_eq!(resumed.value, expected.value, "{}", name);
+        assert_eq!(resumed.time, expected.time, "{}", name);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(best_path(&path)).unwrap();
+    }
+}
+
+#[test]
+fn checkpoints_only_resume_the_same_search() {
+    let simulator = simulator();
+    let path = checkpoint_path("settings");
+    let options = SearchOptions {
+        horizon: 2500,
+        ..Default::default()
+    };
+    let constraints = Constraints::default();
+    simulator
+        .search_checkpointed(
+            &options,
+            &constraints,
+            CheckpointOptions {
+                path: path.clone(),
+                interval: Duration::ZERO,
+                resume: None,
+            },
+        )
+        .unwrap();
+    assert!(simulator
+        .load_checkpoint(&path, &options, &constraints)
+        .is_ok());
+
+    let other = SearchOptions {
+        horizon: 3000,
+        ..options
+    };
+    assert!(matches!(
+        simulator.load_checkpoint(&path, &other, &constraints),
+        Err(CheckpointError::Mismatch(_))
+    ));
+
+    fs::write(&path, "not a checkpoint").unwrap();
+    assert!(matches!(
+        simulator.load_checkpoint(&path, &options, &constraints),
+        Err(CheckpointError::Io(..))
+    ));
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(best_path(&path)).unwrap();
+}
+
+#[test]
+fn checkpoints_only_resume_with_the_same_action_table() {
+    let simulator = simulator();
+    let path = checkpoint_path("table");
+    let options = SearchOptions {
+        horizon: 2500,
+        ..Default::default()
+    };
+    let constraints = Constraints::default();
+    simulator
+        .search_checkpointed(
+            &options,
+            &constraints,
+            CheckpointOptions {
+                path: path.clone(),
+                interval: Duration::ZERO,
+                resume: None,
+            },
+        )
+        .unwrap();
+
+    let table = fs::read_to_string("actions.json").unwrap();
+    let fight_or_flight = "\"cooldown_group\": 11,\n    \"cast\": 800,\n    \"recast\": 60000,";
+    assert!(table.contains(fight_or_flight));
+    let actions = path.with_extension("json");
+    fs::write(
+        &actions,
+        table.replace(
+            fight_or_flight,
+            "\"cooldown_group\": 11,\n    \"cast\": 800,\n    \"recast\": 1000,",
+        ),
+    )
+    .unwrap();
+    let changed = Simulator::load(&actions, Path::new("stats.json")).unwrap();
+    assert!(matches!(
+        changed.load_checkpoint(&path, &options, &constraints),
+        Err(CheckpointError::Mismatch(_))
+    ));
+
+    fs::remove_file(&actions).unwrap();
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(best_path(&path)).unwrap();
+}
+
+/// Overwrites the step of the first state after the root, which the root reaches directly
+fn replace_first_step(path: &Path, action_name: ActionName) {
+    let mut bytes = fs::read(path).unwrap();
+    let u32_at = |bytes: &[u8], offset: usize| {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    };
+    // Magic, settings, node count, peak heap, best state and state count come first
+    let states = 8 + 4 + u32_at(&bytes, 8) as usize + 8 + 8 + 12 + 4;
+    // Damage, parent, step kind and step value of every state
+    let first = states + 16;
+    assert_eq!(u32_at(&bytes, first + 4), 0);
+    bytes[first + 8..first + 12].copy_from_slice(&0u32.to_le_bytes());
+    bytes[first + 12..first + 16].copy_from_slice(&(action_name as u32).to_le_bytes());
+    fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn broken_histories_do_not_resume() {
+    let simulator = simulator();
+    let path = checkpoint_path("history");
+    let options = SearchOptions {
+        horizon: 2500,
+        ..Default::default()
+    };
+    let constraints = Constraints {
+        prefix: vec![Step::Action(ActionName::FastBlade)],
+        ..Default::default()
+    };
+    let save = || {
+        simulator
+            .search_checkpointed(
+                &options,
+                &constraints,
+                CheckpointOptions {
+                    path: path.clone(),
+                    interval: Duration::ZERO,
+                    resume: None,
+                },
+            )
+            .unwrap();
+    };
+
+    // Blade of Faith cannot open a rotation
+    save();
+    replace_first_step(&path, ActionName::BladeOfFaith);
+    assert!(matches!(
+        simulator.load_checkpoint(&path, &options, &constraints),
+        Err(CheckpointError::Replay(_))
+    ));
+
+    // Fight or Flight can, but the prefix starts with Fast Blade
+    save();
+    replace_first_step(&path, ActionName::FightOrFlight);
+    assert!(matches!(
+        simulator.load_checkpoint(&path, &options, &constraints),
+        Err(CheckpointError::Constraints(_))
+    ));
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(best_path(&path)).unwrap();
+}
+
+#[test]
+fn failing_to_save_stops_the_search() {
+    let simulator = simulator();
+    let path = std::env::temp_dir()
+        .join(format!("ffxiv-rotation-{}-missing", std::process::id()))
+        .join("run.ck");
+    let result = simulator.search_checkpointed(
+        &SearchOptions {
+            horizon: 2500,
+            ..Default::default()
+        },
+        &Constraints::default(),
+        CheckpointOptions {
+            path,
+            interval: Duration::ZERO,
+            resume: None,
+        },
+    );
+    assert!(matches!(result, Err(CheckpointError::Save(..))));
+}
+
+#[test]
+fn corrupt_lengths_give_an_error() {
+    let simulator = simulator();
+    let path = checkpoint_path("lengths");
+    let options = SearchOptions::default();
+    let constraints = Constraints::default();
+    // Settings that claim to be 4 GiB long
+    let mut bytes = b"ffxivck2".to_vec();
+    bytes.extend(u32::MAX.to_le_bytes());
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        simulator.load_checkpoint(&path, &options, &constraints),
+        Err(CheckpointError::Io(..))
+    ));
+
+    // No settings, no nodes and no best state, then a state count far beyond the one state in
+    // the file
+    let mut bytes = b"ffxivck2".to_vec();
+    bytes.extend([0; 4 + 16 + 12]);
+    bytes.extend(u32::MAX.to_le_bytes());
+    bytes.extend([0; 16]);
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        simulator.load_checkpoint(&path, &options, &constraints),
+        Err(CheckpointError::Io(..))
+    ));
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{ActionName, Simulator, Step};
+
+#[test]
+fn blade_of_truth_works_outside_its_combo() {
+    let simulator = simulator();
+    let player = simulator
+        .apply(&simulator.player(), Step::Action(ActionName::BladeOfTruth))
+        .unwrap();
+    assert!(player.damage() > 0);
+    // Without Blade of Faith before it, it does not lead into Blade of Valor
+    assert!(simulator
+        .apply(&player, Step::Action(ActionName::BladeOfValor))
+        .is_err());
+}
+
+#[test]
+fn blade_of_faith_and_valor_still_need_their_combo() {
+    let simulator = simulator();
+    for action_name in [ActionName::BladeOfFaith, ActionName::BladeOfValor] {
+        assert!(simulator
+            .apply(&simulator.player(), Step::Action(action_name))
+            .is_err());
+    }
+}
+
+/// Damage of each step when playing the actions in order from a fresh player
+fn hits(simulator: &Simulator, actions: &[ActionName]) -> Vec<u32> {
+    let mut player = simulator.player();
+    actions
+        .iter()
+        .map(|action_name| {
+            let next = simulator
+                .apply(&player, Step::Action(*action_name))
+                .unwrap();
+            let damage = next.damage() - player.damage();
+            player = next;
+            damage
+        })
+        .collect()
+}
+
+#[test]
+fn blades_in_combo_hit_with_or_without_requiescat() {
+    use ActionName::*;
+    let simulator = simulator();
+    // All four Requiescat stacks are gone by Blade of Valor, which falls back to its 400 potency
+    let damage = hits(
+        &simulator,
+        &[
+            Requiescat,
+            HolySpirit,
+            Confiteor,
+            BladeOfFaith,
+            BladeOfTruth,
+            BladeOfValor,
+        ],
+    );
+    assert_eq!(damage[5], 12375);
+    // With a stack left it gets its 900 potency, the same as Confiteor under Requiescat
+    let damage = hits(
+        &simulator,
+        &[
+            Requiescat,
+            Confiteor,
+            BladeOfFaith,
+            BladeOfTruth,
+            BladeOfValor,
+        ],
+    );
+    assert_eq!(damage[1], 27847);
+    assert_eq!(damage[4], 27847);
+}
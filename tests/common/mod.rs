@@ -0,0 +1,57 @@
+//! Helpers shared by the integration tests, each test crate using only some of them
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use enum_map::Enum;
+use ffxiv_rotation::{ActionName, Player, Simulator, Step};
+
+pub const TEN_MINUTES: u32 = 600_000;
+
+/// The simulator for the bundled action table and stats
+pub fn simulator() -> Simulator {
+    Simulator::load(Path::new("actions.json"), Path::new("stats.json")).unwrap()
+}
+
+/// Every step a brute force tries from the player: each action, or waiting for the next cooldown
+pub fn moves(player: &Player) -> Vec<Step> {
+    (0..ActionName::LENGTH)
+        .map(ActionName::from_usize)
+        .filter(|action_name| *action_name != ActionName::None)
+        .map(Step::Action)
+        .chain(player.next_ready_time().map(Step::Wait))
+        .collect()
+}
+
+/// When the step is pressed from the player, after waiting for its cooldowns
+pub fn press_time(simulator: &Simulator, player: &Player, step: Step) -> u32 {
+    match step {
+        Step::Action(action_name) => {
+            player.time() + player.ready_in(&simulator.actions()[action_name])
+        }
+        Step::Wait(_) => player.time(),
+    }
+}
+
+/// Repeats the basic combo, optionally idling after every GCD, until the time runs out
+pub fn filler(simulator: &Simulator, idle: u32, until: u32) -> Player {
+    let combo = [
+        ActionName::FastBlade,
+        ActionName::RiotBlade,
+        ActionName::RoyalAuthority,
+    ];
+    let mut player = simulator.player();
+    for action_name in combo.iter().cycle() {
+        let mut next = simulator
+            .apply(&player, Step::Action(*action_name))
+            .unwrap();
+        if idle > 0 {
+            next = simulator.apply(&next, Step::Wait(idle)).unwrap();
+        }
+        if next.time() > until {
+            break;
+        }
+        player = next;
+    }
+    player
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use common::{moves, press_time, simulator};
+use ffxiv_rotation::{
+    constraints::{ActionWindow, UseLimit},
+    ActionName, Constraints, Player, SearchOptions, SearchResult, Simulator, Step, Strategy,
+};
+
+fn window(action: ActionName, start: u32, end: u32) -> ActionWindow {
+    ActionWindow { action, start, end }
+}
+
+/// Whether the pressed steps so far break a rule that no later step can fix
+fn broken(constraints: &Constraints, pressed: &[(Step, u32)]) -> bool {
+    let prefix = pressed
+        .iter()
+        .zip(&constraints.prefix)
+        .any(|((step, _), prefix_step)| step != prefix_step);
+    let forbidden = pressed.iter().any(|(step, time)| {
+        constraints
+            .forbidden
+            .iter()
+            .any(|window| *step == Step::Action(window.action) && window.contains(*time))
+    });
+    let overused = constraints.max_uses.iter().any(|limit| {
+        let uses = pressed
+            .iter()
+            .filter(|(step, _)| *step == Step::Action(limit.action))
+            .count();
+        uses > limit.uses as usize
+    });
+    prefix || forbidden || overused
+}
+
+fn complete(constraints: &Constraints, pressed: &[(Step, u32)]) -> bool {
+    pressed.len() >= constraints.prefix.len()
+        && constraints.required.iter().all(|window| {
+            pressed
+                .iter()
+                .any(|(step, time)| *step == Step::Action(window.action) && window.contains(*time))
+        })
+}
+
+/// Most damage of any rotation ending by the horizon that follows the constraints
+fn brute_force(
+    simulator: &Simulator,
+    player: &Player,
+    constraints: &Constraints,
+    horizon: u32,
+    pressed: &mut Vec<(Step, u32)>,
+) -> Option<u32> {
+    let mut best = complete(constraints, pressed).then(|| player.damage());
+    for step in moves(player) {
+        let next = match simulator.apply(player, step) {
+            Ok(next) if next.time() <= horizon => next,
+            _ => continue,
+        };
+        pressed.push((step, press_time(simulator, player, step)));
+        if !broken(constraints, pressed) {
+            let damage = brute_force(simulator, &next, constraints, horizon, pressed);
+            best = best.max(damage);
+        }
+        pressed.pop();
+    }
+    best
+}
+
+fn pressed(result: &SearchResult) -> Vec<(Step, u32)> {
+    result
+        .steps
+        .iter()
+        .map(|record| (record.step, record.time))
+        .collect()
+}
+
+#[test]
+fn parses_windows_and_limits() {
+    assert_eq!(
+        "Fight or Flight@5s-10s".parse(),
+        Ok(window(ActionName::FightOrFlight, 5000, 10000))
+    );
+    assert_eq!(
+        "Intervene@40s-50s".parse(),
+        Ok(window(ActionName::Intervene, 40000, 50000))
+    );
+    assert_eq!(
+        "Intervene=2".parse(),
+        Ok(UseLimit {
+            action: ActionName::Intervene,
+            uses: 2
+        })
+    );
+    assert!("Intervene@50s-40s".parse::<ActionWindow>().is_err());
+    assert!("Intervene".parse::<ActionWindow>().is_err());
+    assert!("Nothing=2".parse::<UseLimit>().is_err());
+}
+
+#[test]
+fn constrained_search_matches_brute_force() {
+    let simulator = simulator();
+    let horizon = 4000;
+    let constraints = Constraints {
+        prefix: vec![Step::Action(ActionName::FastBlade)],
+        forbidden: vec![window(ActionName::Intervene, 0, 3000)],
+        required: vec![window(ActionName::FightOrFlight, 1000, 3000)],
+        max_uses: vec![UseLimit {
+            action: ActionName::Expiacion,
+            uses: 0,
+        }],
+    };
+    let expected = brute_force(
+        &simulator,
+        &simulator.player(),
+        &constraints,
+        horizon,
+        &mut vec![],
+    )
+    .unwrap();
+    let unconstrained = simulator.search(&SearchOptions {
+        horizon,
+        ..Default::default()
+    });
+    assert!(expected < unconstrained.value);
+
+    for strategy in [
+        Strategy::BestFirst,
+        Strategy::Bound,
+        Strategy::Parallel { threads: 2 },
+    ] {
+        let options = SearchOptions {
+            horizon,
+            strategy,
+            ..Default::default()
+        };
+        let result = simulator
+            .search_constrained(&options, &constraints)
+            .unwrap();
+        assert_eq!(result.value, expected, "{:?}", strategy);
+        let pressed = pressed(&result);
+        assert!(!broken(&constraints, &pressed));
+        assert!(complete(&constraints, &pressed));
+    }
+}
+
+#[test]
+fn beam_follows_the_constraints() {
+    let simulator = simulator();
+    let constraints = Constraints {
+        prefix: vec![
+            Step::Action(ActionName::FastBlade),
+            Step::Action(ActionName::RiotBlade),
+            Step::Action(ActionName::RoyalAuthority),
+            Step::Action(ActionName::FightOrFlight),
+        ],
+        forbidden: vec![window(ActionName::Intervene, 30000, 60000)],
+        required: vec![window(ActionName::Requiescat, 20000, 40000)],
+        max_uses: vec![UseLimit {
+            action: ActionName::CircleOfScorn,
+            uses: 1,
+        }],
+    };
+    let options = SearchOptions {
+        horizon: 60000,
+        strategy: Strategy::Beam {
+            width: 20,
+            score: Default::default(),
+        },
+        ..Default::default()
+    };
+    let result = simulator
+        .search_constrained(&options, &constraints)
+        .unwrap();
+    let pressed = pressed(&result);
+    assert!(!broken(&constraints, &pressed));
+    assert!(complete(&constraints, &pressed));
+}
+
+#[test]
+fn impossible_constraints_give_no_rotation() {
+    let simulator = simulator();
+    let constraints = Constraints {
+        required: vec![window(ActionName::FightOrFlight, 5000, 6000)],
+        ..Default::default()
+    };
+    for strategy in [
+        Strategy::BestFirst,
+        Strategy::Bound,
+        Strategy::Beam {
+            width: 10,
+            score: Default::default(),
+        },
+        Strategy::Parallel { threads: 2 },
+    ] {
+        let options = SearchOptions {
+            horizon: 4000,
+            strategy,
+            ..Default::default()
+        };
+        assert_eq!(
+            simulator.search_constrained(&options, &constraints),
+            None,
+            "{:?}",
+            strategy
+        );
+    }
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::simulator;
+use ffxiv_rotation::{
+    report, CycleOptions, Objective, SearchOptions, Simulator, Step, Strategy, SERVER_TICK,
+};
+
+#[test]
+fn cycle_key_ignores_whole_ticks() {
+    let simulator = simulator();
+    let player = simulator.player();
+    let later = simulator
+        .apply(&player, Step::Wait(2 * SERVER_TICK))
+        .unwrap();
+    let off_tick = simulator.apply(&player, Step::Wait(1000)).unwrap();
+    assert_ne!(later.state_key(), player.state_key());
+    assert_eq!(later.cycle_key(), player.cycle_key());
+    assert_ne!(off_tick.cycle_key(), player.cycle_key());
+}
+
+#[test]
+fn cycle_repeats_with_the_same_damage() {
+    let simulator = simulator();
+    let result = simulator
+        .cycle(&CycleOptions {
+            width: 10,
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(result.time > 0);
+    assert!((result.dps() - result.damage as f64 / result.time as f64 * 1000.0).abs() < 1e-9);
+
+    let opener: Vec<Step> = result.opener.iter().map(|record| record.step).collect();
+    let pass: Vec<Step> = result.steps.iter().map(|record| record.step).collect();
+    let mut player = simulator.play(&opener).unwrap();
+    assert_eq!(player.time(), result.start);
+    let anchor = player.cycle_key();
+    for _ in 0..3 {
+        let start = player.clone();
+        for step in &pass {
+            player = simulator.apply(&player, *step).unwrap();
+        }
+        assert_eq!(player.cycle_key(), anchor);
+        assert_eq!(player.damage() - start.damage(), result.damage);
+        assert_eq!(player.time() - start.time(), result.time);
+    }
+}
+
+#[test]
+fn loops_report_the_periods_they_span() {
+    let simulator = simulator();
+    let options = CycleOptions {
+        period: 20000,
+        width: 3,
+        ..Default::default()
+    };
+    let result = simulator.cycle(&options).unwrap();
+    // Every pass takes at least a period and less than two
+    assert_eq!(result.period % options.period, 0);
+    assert!(result.time >= result.period);
+    assert!(result.time < 2 * result.period);
+    // This loop only comes back in step after several search periods
+    assert!(result.period > options.period, "{:?}", result.period);
+    let text = report::cycle_text(&result);
+    let expected = format!(
+        "over {:.2}s of search periods",
+        result.period as f64 / 1000.0
+    );
+    assert!(text.contains(&expected), "{}", text);
+}
+
+/// Mean damage per second of the best loop cut straight from a beam rotation from a fresh
+/// player, playing each period-long stretch over and over until its state repeats
+fn sliced_dps(simulator: &Simulator, options: &CycleOptions) -> f64 {
+    let rotation = simulator.search(&SearchOptions {
+        horizon: 5 * options.period,
+        objective: Objective::Damage,
+        strategy: Strategy::Beam {
+            width: options.width,
+            score: options.score,
+        },
+    });
+    let mut players = vec![simulator.player()];
+    for record in &rotation.steps {
+        let next = simulator
+            .apply(players.last().unwrap(), record.step)
+            .unwrap();
+        players.push(next);
+    }
+    let mut best: f64 = 0.0;
+    for (index, start) in players.iter().enumerate() {
+        let Some(length) = players[index..]
+            .iter()
+            .position(|other| other.time() >= start.time() + options.period)
+        else {
+            break;
+        };
+        let segment = &rotation.steps[index..index + length];
+        let mut player = start.clone();
+        let mut seen = HashMap::new();
+        for _ in 0..16 {
+            if let Some(&(time, damage)) = seen.get(&player.cycle_key()) {
+                let dps = (player.damage() - damage) as f64 / (player.time() - time) as f64;
+                best = best.max(dps * 1000.0);
+                break;
+            }
+            seen.insert(player.cycle_key(), (player.time(), player.damage()));
+            match segment.iter().try_fold(player, |player, record| {
+                simulator.apply(&player, record.step)
+            }) {
+                Ok(next) => player = next,
+                Err(_) => break,
+            }
+        }
+    }
+    best
+}
+
+#[test]
+fn searching_from_the_settled_state_beats_the_sliced_loop() {
+    let simulator = simulator();
+    let options = CycleOptions {
+        width: 5,
+        ..Default::default()
+    };
+    let sliced = sliced_dps(&simulator, &options);
+    let result = simulator.cycle(&options).unwrap();
+    assert!(result.dps() > sliced, "{} vs {}", result.dps(), sliced);
+}
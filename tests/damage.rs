@@ -0,0 +1,48 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{stats::Stats, status::Buffs, ActionName, Step};
+
+// Potencies are stored in tenths, so this is a 200 potency hit
+const POTENCY: u32 = 2000;
+
+#[test]
+fn multipliers_match_the_level_90_formulas() {
+    let stats = Stats::default();
+    assert_eq!(stats.weapon_damage_multiplier(), 171);
+    assert_eq!(stats.attack_power_multiplier(), 1276);
+    assert_eq!(stats.determination_multiplier(), 1122);
+    assert_eq!(stats.tenacity_multiplier(), 1018);
+    assert_eq!(stats.critical_hit_rate(), 279);
+    assert_eq!(stats.critical_hit_multiplier(), 1629);
+    assert_eq!(stats.direct_hit_rate(), 225);
+    assert_eq!(stats.base_damage(POTENCY), 4982);
+}
+
+#[test]
+fn expected_damage_weights_critical_and_direct_hits() {
+    let stats = Stats::default();
+    // 4982 * (1 + 0.279 * 0.629) * (1 + 0.225 * 0.25)
+    assert_eq!(stats.expected_damage(POTENCY, &Buffs::default()), 6186);
+    let damage_up = Buffs {
+        damage: 1.25,
+        ..Default::default()
+    };
+    assert_eq!(stats.expected_damage(POTENCY, &damage_up), 7732);
+    // Critical hit rate is capped at certain
+    let certain_critical = Buffs {
+        critical_hit_rate: 1000,
+        ..Default::default()
+    };
+    assert_eq!(stats.expected_damage(POTENCY, &certain_critical), 8572);
+}
+
+#[test]
+fn hits_deal_the_expected_damage_of_their_potency() {
+    let simulator = simulator();
+    assert_eq!(simulator.stats(), Stats::default());
+    let player = simulator
+        .apply(&simulator.player(), Step::Action(ActionName::FastBlade))
+        .unwrap();
+    assert_eq!(player.damage(), 6186);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{stats::Stats, status::Buffs, ActionName, Player, Simulator, Step};
+
+// Circle of Scorn ticks for 30 potency, stored in tenths, over 15 s
+const CIRCLE_TICK_POTENCY: u32 = 300;
+
+/// Damage the ticks deal while the player waits
+fn ticks(simulator: &Simulator, player: &Player, time: u32) -> u32 {
+    simulator.apply(player, Step::Wait(time)).unwrap().damage() - player.damage()
+}
+
+#[test]
+fn ticks_land_on_server_ticks_until_the_status_runs_out() {
+    let simulator = simulator();
+    let tick = Stats::default().expected_damage(CIRCLE_TICK_POTENCY, &Buffs::default());
+    let player = simulator
+        .play(&[Step::Action(ActionName::CircleOfScorn)])
+        .unwrap();
+    // Applied at 0, so the first tick lands at 3 s
+    let to_first_tick = 3000 - player.time();
+    assert_eq!(ticks(&simulator, &player, to_first_tick - 1), 0);
+    assert_eq!(ticks(&simulator, &player, to_first_tick), tick);
+    // Ticks at 3, 6, 9, 12 and 15 s, then nothing
+    assert_eq!(ticks(&simulator, &player, 30000), 5 * tick);
+}
+
+#[test]
+fn ticks_snapshot_the_buffs_active_when_applied() {
+    let simulator = simulator();
+    let unbuffed = Stats::default().expected_damage(CIRCLE_TICK_POTENCY, &Buffs::default());
+    let buffed = Stats::default().expected_damage(
+        CIRCLE_TICK_POTENCY,
+        &Buffs {
+            damage: 1.25,
+            ..Default::default()
+        },
+    );
+
+    let before = simulator
+        .play(&[
+            Step::Action(ActionName::FightOrFlight),
+            Step::Action(ActionName::CircleOfScorn),
+        ])
+        .unwrap();
+    assert_eq!(ticks(&simulator, &before, 15000), 5 * buffed);
+
+    // Fight or Flight pressed afterwards does not raise the ticks
+    let after = simulator
+        .play(&[
+            Step::Action(ActionName::CircleOfScorn),
+            Step::Action(ActionName::FightOrFlight),
+        ])
+        .unwrap();
+    assert_eq!(ticks(&simulator, &after, 15000), 5 * unbuffed);
+}
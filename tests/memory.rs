@@ -0,0 +1,66 @@
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use common::simulator;
+use ffxiv_rotation::{SearchOptions, SearchResult, Simulator, Strategy};
+
+/// Counts the bytes allocated through it, so a test can compare the peak memory of searches. It
+/// covers the whole test binary, which is why this file holds a single test.
+struct Counting;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Runs the search and returns its result with the most memory it held above what was allocated
+/// before
+fn peak_memory(simulator: &Simulator, options: &SearchOptions) -> (SearchResult, usize) {
+    let before = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let result = simulator.search(options);
+    (result, PEAK.load(Ordering::Relaxed) - before)
+}
+
+#[test]
+fn parallel_search_stays_within_a_few_times_the_memory_of_best_first() {
+    let simulator = simulator();
+    let options = SearchOptions {
+        horizon: 6000,
+        ..Default::default()
+    };
+    let (expected, best_first) = peak_memory(&simulator, &options);
+    let (result, parallel) = peak_memory(
+        &simulator,
+        &SearchOptions {
+            strategy: Strategy::Parallel { threads: 1 },
+            ..options
+        },
+    );
+    assert_eq!(result.value, expected.value);
+    // The visited tables cost the same, the pending states keep only their keys
+    assert!(
+        parallel < 3 * best_first,
+        "parallel peaked at {} bytes, best-first at {}",
+        parallel,
+        best_first
+    );
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{moves, simulator};
+use ffxiv_rotation::{ActionName, Objective, Player, SearchOptions, Simulator, Step, Strategy};
+
+/// Best objective value over every step sequence ending by the horizon, found by trying them all
+fn brute_force(simulator: &Simulator, player: &Player, options: &SearchOptions, value: u32) -> u32 {
+    let mut best = value;
+    for step in moves(player) {
+        let next = match simulator.apply(player, step) {
+            Ok(next) if next.time() <= options.horizon => next,
+            _ => continue,
+        };
+        let gained = match options.objective {
+            Objective::Burst { start, end } => {
+                player.damage_within(&step, simulator.actions(), start, end)
+            }
+            _ => next.damage() - player.damage(),
+        };
+        let value = value + gained;
+        best = best.max(brute_force(simulator, &next, options, value));
+    }
+    best
+}
+
+fn check(options: SearchOptions) {
+    let simulator = simulator();
+    let expected = brute_force(&simulator, &simulator.player(), &options, 0);
+    for strategy in [Strategy::BestFirst, Strategy::Bound] {
+        let options = SearchOptions {
+            strategy,
+            ..options
+        };
+        let result = simulator.search(&options);
+        assert_eq!(result.value, expected, "{:?}", options);
+
+        // The reported rotation actually reaches the reported value
+        let steps: Vec<Step> = result.steps.iter().map(|record| record.step).collect();
+        let player = simulator.play(&steps).unwrap();
+        assert_eq!(player.damage(), result.damage);
+        assert!(player.time() <= options.horizon);
+        if let Objective::Burst { start, end } = options.objective {
+            let mut player = simulator.player();
+            let mut window = 0;
+            for step in &steps {
+                window += player.damage_within(step, simulator.actions(), start, end);
+                player = simulator.apply(&player, *step).unwrap();
+            }
+            assert_eq!(window, result.value);
+        } else {
+            assert_eq!(result.damage, result.value);
+        }
+    }
+}
+
+#[test]
+fn damage_matches_brute_force() {
+    for horizon in [1000, 2500, 3300, 4000, 6000] {
+        check(SearchOptions {
+            horizon,
+            objective: Objective::Damage,
+            ..Default::default()
+        });
+    }
+}
+
+#[test]
+fn dps_matches_brute_force() {
+    check(SearchOptions {
+        horizon: 3300,
+        objective: Objective::Dps,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn burst_matches_brute_force() {
+    check(SearchOptions {
+        horizon: 4000,
+        objective: Objective::Burst {
+            start: 1000,
+            end: 3000,
+        },
+        ..Default::default()
+    });
+}
+
+#[test]
+fn burst_counts_ticks_of_damage_over_time_applied_before_the_window() {
+    check(SearchOptions {
+        horizon: 6000,
+        objective: Objective::Burst {
+            start: 2500,
+            end: 6000,
+        },
+        ..Default::default()
+    });
+
+    let simulator = simulator();
+    let circle = Step::Action(ActionName::CircleOfScorn);
+    let player = simulator.player();
+    // The hit lands at 0, before the window
+    assert_eq!(
+        player.damage_within(&circle, simulator.actions(), 1000, 4000),
+        0
+    );
+    let player = simulator.apply(&player, circle).unwrap();
+    let wait = Step::Wait(6000);
+    let ticks = simulator.apply(&player, wait).unwrap().damage() - player.damage();
+    assert!(ticks > 0);
+    // Of the ticks at 3 s and 6 s only the first lands inside the window
+    assert_eq!(
+        player.damage_within(&wait, simulator.actions(), 1000, 4000),
+        ticks / 2
+    );
+}
+
+#[test]
+fn time_to_reaches_target_first() {
+    let simulator = simulator();
+    let earliest = [Strategy::BestFirst, Strategy::Bound].map(|strategy| {
+        let result = simulator.search(&SearchOptions {
+            horizon: 4000,
+            objective: Objective::TimeTo(40000),
+            strategy,
+        });
+        assert!(result.damage >= 40000);
+        result.time
+    });
+    assert_eq!(earliest[0], earliest[1]);
+    // No rotation ending any earlier deals as much damage
+    let earlier = SearchOptions {
+        horizon: earliest[0] - 1,
+        ..Default::default()
+    };
+    assert!(brute_force(&simulator, &simulator.player(), &earlier, 0) < 40000);
+}
+
+#[test]
+fn bound_handles_twelve_seconds() {
+    let simulator = simulator();
+    let options = SearchOptions {
+        horizon: 12000,
+        objective: Objective::Damage,
+        strategy: Strategy::Bound,
+    };
+    let result = simulator.search(&options);
+    // The horizon `DamageBound` documents as cheap, with room for the search order to change
+    assert!(result.nodes < 200_000, "{} nodes", result.nodes);
+    let beam = simulator.search(&SearchOptions {
+        strategy: Strategy::Beam {
+            width: 50,
+            score: Default::default(),
+        },
+        ..options
+    });
+    assert!(result.value >= beam.value);
+    let steps: Vec<Step> = result.steps.iter().map(|record| record.step).collect();
+    assert_eq!(simulator.play(&steps).unwrap().damage(), result.damage);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use std::cmp::Ordering;
+
+use common::{filler, simulator, TEN_MINUTES};
+use ffxiv_rotation::{ActionName, Player, Step};
+
+fn dps(player: &Player) -> f64 {
+    player.damage() as f64 / player.time() as f64
+}
+
+#[test]
+fn ten_minute_states_order_by_dps() {
+    let simulator = simulator();
+    let fast = filler(&simulator, 0, TEN_MINUTES);
+    let slow = filler(&simulator, 500, TEN_MINUTES);
+    // Both products are far beyond u32::MAX
+    assert!(fast.damage() as u64 * slow.time() as u64 > u32::MAX as u64);
+    assert!(dps(&fast) > dps(&slow));
+    assert_eq!(fast.cmp(&slow), Ordering::Greater);
+    assert_eq!(slow.cmp(&fast), Ordering::Less);
+}
+
+#[test]
+fn ordering_agrees_with_dps_across_horizons() {
+    let simulator = simulator();
+    let players: Vec<Player> = [60_000, 180_000, 420_000, TEN_MINUTES]
+        .into_iter()
+        .flat_map(|until| [0, 300, 1200].map(|idle| filler(&simulator, idle, until)))
+        .collect();
+    for a in &players {
+        for b in &players {
+            let expected = dps(a).partial_cmp(&dps(b)).unwrap();
+            if expected != Ordering::Equal {
+                assert_eq!(a.cmp(b), expected, "{} vs {}", dps(a), dps(b));
+            }
+        }
+    }
+}
+
+#[test]
+fn equal_dps_prefers_the_later_state() {
+    let simulator = simulator();
+    let player = simulator.player();
+    let early = simulator.apply(&player, Step::Wait(1000)).unwrap();
+    let late = simulator.apply(&player, Step::Wait(TEN_MINUTES)).unwrap();
+    assert_eq!(late.cmp(&early), Ordering::Greater);
+    assert_eq!(early.cmp(&player), Ordering::Greater);
+    assert_eq!(late.cmp(&late.clone()), Ordering::Equal);
+}
+
+#[test]
+fn only_equal_players_compare_equal() {
+    let simulator = simulator();
+    let buffed = simulator
+        .apply(&simulator.player(), Step::Action(ActionName::FightOrFlight))
+        .unwrap();
+    // Same time, damage and MP, but without the buff
+    let idle = simulator
+        .apply(&simulator.player(), Step::Wait(buffed.time()))
+        .unwrap();
+    assert_eq!(
+        (idle.time(), idle.damage()),
+        (buffed.time(), buffed.damage())
+    );
+    assert_ne!(idle, buffed);
+    assert_ne!(idle.cmp(&buffed), Ordering::Equal);
+    assert_eq!(idle.cmp(&buffed), buffed.cmp(&idle).reverse());
+}
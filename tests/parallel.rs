@@ -0,0 +1,78 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{Objective, SearchOptions, Step, Strategy};
+
+fn objectives() -> [Objective; 4] {
+    [
+        Objective::Damage,
+        Objective::Dps,
+        Objective::Burst {
+            start: 1000,
+            end: 3000,
+        },
+        Objective::TimeTo(60000),
+    ]
+}
+
+#[test]
+fn parses_parallel_strategies() {
+    assert_eq!("parallel:3".parse(), Ok(Strategy::Parallel { threads: 3 }));
+    assert!(matches!(
+        "parallel".parse(),
+        Ok(Strategy::Parallel { threads }) if threads > 0
+    ));
+    assert!("parallel:0".parse::<Strategy>().is_err());
+    assert!("parallel:two".parse::<Strategy>().is_err());
+    assert!("parallel:2:4".parse::<Strategy>().is_err());
+}
+
+#[test]
+fn parallel_matches_best_first() {
+    let simulator = simulator();
+    for objective in objectives() {
+        let options = SearchOptions {
+            horizon: 4000,
+            objective,
+            ..Default::default()
+        };
+        let expected = simulator.search(&options);
+        let result = simulator.search(&SearchOptions {
+            strategy: Strategy::Parallel { threads: 2 },
+            ..options
+        });
+        if let Objective::TimeTo(target) = objective {
+            // Any state over the target at the earliest time will do
+            assert!(result.value >= target);
+            assert_eq!(result.time, expected.time);
+        } else {
+            assert_eq!(result.value, expected.value, "{:?}", objective);
+        }
+
+        let steps: Vec<Step> = result.steps.iter().map(|record| record.step).collect();
+        let player = simulator.play(&steps).unwrap();
+        assert_eq!(player.damage(), result.damage);
+        assert!(player.time() <= options.horizon);
+    }
+}
+
+#[test]
+fn parallel_is_deterministic() {
+    let simulator = simulator();
+    for objective in objectives() {
+        let results: Vec<_> = [1, 1, 2, 3, 8]
+            .map(|threads| {
+                simulator.search(&SearchOptions {
+                    horizon: 3300,
+                    objective,
+                    strategy: Strategy::Parallel { threads },
+                })
+            })
+            .into_iter()
+            .map(|result| (result.steps, result.value, result.nodes))
+            .collect();
+        for result in &results[1..] {
+            assert_eq!(*result, results[0], "{:?}", objective);
+        }
+    }
+}
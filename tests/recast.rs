@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use ffxiv_rotation::{
+    stats::load_stats, table::load_actions, ActionName, Simulator, Step, GLOBAL_COOLDOWN,
+    GLOBAL_COOLDOWN_GROUP,
+};
+
+/// Goring Blade's own cooldown group
+const GORING_BLADE_GROUP: u32 = 13;
+
+#[test]
+fn speed_only_shortens_the_global_cooldown() {
+    let mut stats = load_stats(Path::new("stats.json")).unwrap();
+    stats.skill_speed = 3000;
+    let simulator = Simulator::new(load_actions(Path::new("actions.json")).unwrap(), stats);
+    let player = simulator
+        .apply(&simulator.player(), Step::Action(ActionName::GoringBlade))
+        .unwrap();
+
+    // Both timers have been ticking since the press at time 0
+    let goring_blade = player.recast_timer(GORING_BLADE_GROUP).unwrap();
+    assert_eq!(goring_blade.cooldown() + player.time(), 60000);
+    let global = player.recast_timer(GLOBAL_COOLDOWN_GROUP).unwrap();
+    assert!(global.cooldown() + player.time() < GLOBAL_COOLDOWN);
+}
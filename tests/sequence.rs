@@ -0,0 +1,104 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{
+    report,
+    sequence::{parse_sequence, play_sequence, LineError, SequenceError},
+    ActionName, Step,
+};
+
+/// The error of playing the text, which must parse
+fn play_error(text: &str) -> SequenceError {
+    let simulator = simulator();
+    let lines = parse_sequence(text).unwrap();
+    play_sequence(simulator.actions(), simulator.stats(), &lines).unwrap_err()
+}
+
+#[test]
+fn parse_errors_name_the_line_counting_comments_and_blanks() {
+    let error = parse_sequence("# opener\nFast Blade\n\nSlow Blade\n").unwrap_err();
+    assert!(matches!(
+        &error,
+        SequenceError::Line(4, LineError::UnknownAction(name)) if name == "Slow Blade"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "line 4: unknown action name `Slow Blade`"
+    );
+
+    let error = parse_sequence("Fast Blade\nwait 2\n").unwrap_err();
+    assert!(matches!(
+        error,
+        SequenceError::Line(2, LineError::BadTime(_))
+    ));
+
+    let lines =
+        parse_sequence("Fast Blade # start\n  Fight or Flight @ 1.5s\nwait 300ms\n").unwrap();
+    let parsed: Vec<_> = lines
+        .iter()
+        .map(|line| (line.line, line.step, line.weave, line.at))
+        .collect();
+    assert_eq!(
+        parsed,
+        [
+            (1, Step::Action(ActionName::FastBlade), false, None),
+            (2, Step::Action(ActionName::FightOrFlight), true, Some(1500)),
+            (3, Step::Wait(300), false, None),
+        ]
+    );
+}
+
+#[test]
+fn play_errors_name_the_line() {
+    let error = play_error("Fast Blade\nFight or Flight\n");
+    assert!(matches!(
+        error,
+        SequenceError::Line(
+            2,
+            LineError::WeaveMismatch(ActionName::FightOrFlight, false)
+        )
+    ));
+    assert_eq!(
+        error.to_string(),
+        "line 2: Fight or Flight is an oGCD and must be indented"
+    );
+
+    let error = play_error("Fast Blade\nRiot Blade @ 1s\n");
+    assert!(matches!(
+        error,
+        SequenceError::Line(2, LineError::TooLate(ActionName::RiotBlade, 1000, 2500))
+    ));
+
+    let error = play_error("Fast Blade\n\nAtonement\n");
+    assert!(matches!(error, SequenceError::Line(3, LineError::Apply(_))));
+}
+
+#[test]
+fn play_records_each_step_with_its_press_time() {
+    let simulator = simulator();
+    let lines = parse_sequence("Fast Blade\n  Fight or Flight\nRiot Blade @ 3s\n").unwrap();
+    let (steps, player) = play_sequence(simulator.actions(), simulator.stats(), &lines).unwrap();
+    let pressed: Vec<_> = steps
+        .iter()
+        .map(|record| (record.step, record.time))
+        .collect();
+    assert_eq!(
+        pressed,
+        [
+            (Step::Action(ActionName::FastBlade), 0),
+            (Step::Action(ActionName::FightOrFlight), 800),
+            (Step::Action(ActionName::RiotBlade), 3000),
+        ]
+    );
+    let damage: u32 = steps.iter().map(|record| record.damage).sum();
+    assert_eq!(damage, player.damage());
+}
+
+#[test]
+fn play_of_nothing_reports_zero_dps() {
+    let simulator = simulator();
+    let (steps, player) = play_sequence(simulator.actions(), simulator.stats(), &[]).unwrap();
+    let text = report::play_text(&steps, &player);
+    assert!(text.contains("0.0 dps"), "{}", text);
+    assert!(!text.contains("NaN"), "{}", text);
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::simulator;
+use ffxiv_rotation::{ActionName, Step};
+
+#[test]
+fn expired_statuses_and_recovered_cooldowns_leave_no_trace_in_the_key() {
+    let simulator = simulator();
+    // Fight or Flight and Circle of Scorn are ready again and their statuses have run out, so
+    // only the damage tells this player apart from one that waited all along
+    let used = simulator
+        .play(&[
+            Step::Action(ActionName::FightOrFlight),
+            Step::Action(ActionName::CircleOfScorn),
+            Step::Wait(60000),
+        ])
+        .unwrap();
+    let waited = simulator.play(&[Step::Wait(61600)]).unwrap();
+    assert_eq!(used.time(), waited.time());
+    assert!(used.damage() > waited.damage());
+    assert_eq!(used.state_key(), waited.state_key());
+    assert_eq!(used.cycle_key(), waited.cycle_key());
+
+    // Before that, Fight or Flight is still cooling down
+    let used = simulator
+        .play(&[Step::Action(ActionName::FightOrFlight), Step::Wait(30000)])
+        .unwrap();
+    let waited = simulator.play(&[Step::Wait(30800)]).unwrap();
+    assert_ne!(used.state_key(), waited.state_key());
+}
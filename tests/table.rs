@@ -0,0 +1,96 @@
+use std::fs;
+
+use ffxiv_rotation::{
+    status::StatusName,
+    table::{actions_to_json, parse_actions, TableError},
+    ActionName,
+};
+use serde_json::{json, Value};
+
+/// The bundled action table with `change` applied to its JSON
+fn parse_changed(change: impl FnOnce(&mut Value)) -> Result<(), TableError> {
+    let mut table: Value =
+        serde_json::from_str(&fs::read_to_string("actions.json").unwrap()).unwrap();
+    change(&mut table);
+    parse_actions(&table.to_string()).map(|_| ())
+}
+
+#[test]
+fn bundled_table_round_trips() {
+    let actions = parse_actions(&fs::read_to_string("actions.json").unwrap()).unwrap();
+    let again = parse_actions(&actions_to_json(&actions)).unwrap();
+    assert_eq!(again, actions);
+}
+
+#[test]
+fn names_must_match_the_actions() {
+    let error = parse_changed(|table| {
+        let entry = table["FastBlade"].clone();
+        table["SlowBlade"] = entry;
+    })
+    .unwrap_err();
+    assert!(matches!(&error, TableError::UnknownAction(name) if name == "SlowBlade"));
+    assert_eq!(error.to_string(), "unknown action name `SlowBlade`");
+
+    let error = parse_changed(|table| {
+        table.as_object_mut().unwrap().remove("Intervene");
+    })
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        TableError::MissingAction(ActionName::Intervene)
+    ));
+}
+
+#[test]
+fn fields_are_checked() {
+    let error = parse_changed(|table| table["RiotBlade"]["potency"] = json!("high")).unwrap_err();
+    assert!(matches!(error, TableError::Field(ActionName::RiotBlade, _)));
+
+    let error = parse_changed(|table| table["Expiacion"]["recast"] = json!(0)).unwrap_err();
+    assert!(matches!(
+        error,
+        TableError::Invalid(ActionName::Expiacion, "recast must be positive")
+    ));
+    assert_eq!(
+        error.to_string(),
+        "action Expiacion: recast must be positive"
+    );
+}
+
+#[test]
+fn shared_cooldown_groups_must_agree() {
+    // Fast Blade and Riot Blade share the global cooldown
+    let error = parse_changed(|table| table["RiotBlade"]["recast"] = json!(3000)).unwrap_err();
+    assert!(matches!(
+        error,
+        TableError::GroupConflict(_, ActionName::FastBlade, ActionName::RiotBlade)
+    ));
+}
+
+#[test]
+fn each_status_has_one_modifier() {
+    let error = parse_changed(|table| {
+        table["CircleOfScorn"]["statuses"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({
+                "name": "FightOrFlight",
+                "duration": 20000,
+                "modifier": { "DamageUp": 10 }
+            }))
+    })
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        TableError::StatusConflict(
+            StatusName::FightOrFlight,
+            ActionName::FightOrFlight,
+            ActionName::CircleOfScorn
+        )
+    ));
+    assert_eq!(
+        error.to_string(),
+        "actions FightOrFlight and CircleOfScorn apply status FightOrFlight with different modifiers"
+    );
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::path::Path;
+
+use common::simulator;
+use ffxiv_rotation::{
+    table::actions_to_json,
+    xivapi::{import, load_snapshot, Snapshot},
+    ActionName, ANIMATION_LOCK,
+};
+use serde_json::json;
+
+fn row(name: &str, id: u32, cast100ms: u32, recast100ms: u32, mp: u32) -> serde_json::Value {
+    json!({
+        "AdditionalCooldownGroup": 0,
+        "Cast100ms": cast100ms,
+        "CooldownGroup": 58,
+        "ID": id,
+        "Name": name,
+        "PrimaryCostType": if mp > 0 { 3 } else { 0 },
+        "PrimaryCostValue": mp,
+        "Recast100ms": recast100ms,
+        "SecondaryCostType": 0,
+        "SecondaryCostValue": 0
+    })
+}
+
+#[test]
+fn rows_convert_to_milliseconds_and_mp() {
+    let snapshot: Snapshot = serde_json::from_value(json!({
+        "data": [row("Fast Blade", 9, 0, 25, 0), row("Holy Spirit", 7384, 15, 25, 20)]
+    }))
+    .unwrap();
+    let [fast_blade, holy_spirit] = &snapshot.data[..] else {
+        panic!("expected two rows");
+    };
+    assert_eq!(fast_blade.cast(), ANIMATION_LOCK);
+    assert_eq!(fast_blade.recast(), 2500);
+    assert_eq!(fast_blade.mp_cost(), 0);
+    assert_eq!(holy_spirit.cast(), 1500);
+    assert_eq!(holy_spirit.mp_cost(), 1000);
+}
+
+#[test]
+fn import_maps_display_names_and_reports_the_rest() {
+    let simulator = simulator();
+    let snapshot: Snapshot = serde_json::from_value(json!({
+        "data": [
+            row("Holy Spirit", 7384, 20, 25, 20),
+            row("Shield Bash", 16, 0, 25, 0),
+        ]
+    }))
+    .unwrap();
+    let report = import(&snapshot, simulator.actions());
+    assert_eq!(report.unmapped, [(16, "Shield Bash".to_owned())]);
+    assert!(!report.missing.contains(&ActionName::HolySpirit));
+    assert!(report.missing.contains(&ActionName::FastBlade));
+    assert!(!report.missing.contains(&ActionName::None));
+
+    // The snapshot wins where it disagrees with the table
+    assert_eq!(report.mismatches.len(), 1);
+    let mismatch = &report.mismatches[0];
+    assert_eq!(mismatch.action, ActionName::HolySpirit);
+    assert_eq!(mismatch.field, "cast");
+    assert_eq!((&*mismatch.table, &*mismatch.snapshot), ("1500", "2000"));
+    let table: serde_json::Value =
+        serde_json::from_str(&actions_to_json(&report.actions_map)).unwrap();
+    assert_eq!(table["HolySpirit"]["cast"], 2000);
+}
+
+#[test]
+fn bundled_snapshot_agrees_with_the_table() {
+    let simulator = simulator();
+    let snapshot = load_snapshot(Path::new("xivapi.json")).unwrap();
+    let report = import(&snapshot, simulator.actions());
+    assert!(report.missing.is_empty(), "{}", report);
+    assert!(report.mismatches.is_empty(), "{}", report);
+    assert_eq!(&report.actions_map, simulator.actions());
+    assert_eq!(
+        ActionName::from_display_name("Fight or Flight"),
+        Some(ActionName::FightOrFlight)
+    );
+}